@@ -1,5 +1,5 @@
 use leptos::prelude::*;
-use crate::model::Comment;
+use crate::model::{AspectSummary, Comment, CommentError, CommentPage, CommentSort, CommentThread, ModerationStatus, PostedComment, ReanalysisReport, ReviewSummary, ThreadPage, TopKeyphrases, Topic};
 use serde::{Serialize, Deserialize};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -34,90 +34,286 @@ pub async fn get_soroban_config() -> Result<SorobanConfig, ServerFnError> {
     })
 }
 
-/// Create a new comment. Sentiment analysis is performed automatically.
+/// Answer with `error`'s HTTP status and hand it to the client, which gets
+/// it back as `ServerFnError::WrappedServerError`.
+#[cfg(feature = "ssr")]
+fn fail(error: CommentError) -> ServerFnError<CommentError> {
+    if let Some(response) = use_context::<leptos_actix::ResponseOptions>() {
+        if let Ok(status) = actix_web::http::StatusCode::from_u16(error.status_code()) {
+            response.set_status(status);
+        }
+    }
+    error.into()
+}
+
+/// What to show a user for a failed call.
+pub fn error_message(error: &ServerFnError<CommentError>) -> String {
+    match error {
+        ServerFnError::WrappedServerError(e) => e.user_message(),
+        other => other.to_string(),
+    }
+}
+
+/// Create a new comment. Sentiment analysis is performed automatically;
+/// spam (duplicates, advertising, bursts) is rejected with the reason.
 #[server(CreateComment, "/api")]
 pub async fn create_comment(
     post_id: String,
     author_public_key: String,
     content: String,
     parent_id: Option<String>,
-    depth: u8,
-) -> Result<Comment, ServerFnError> {
+) -> Result<PostedComment, ServerFnError<CommentError>> {
     use crate::server::comment_crud;
 
-    comment_crud::create_comment(&post_id, &author_public_key, &content, parent_id, depth)
+    let comment = comment_crud::create_comment(&post_id, &author_public_key, &content, parent_id)
         .await
-        .map_err(|e| ServerFnError::new(e))
+        .map_err(fail)?;
+    Ok(PostedComment { negative_sample: comment.is_negative_sample(), comment: comment.public() })
 }
 
-/// Get all comments for a post.
+/// One page of a post's comments in `sort` order, optionally only those
+/// with one `status` (1=negative, 2=neutral, 3=positive). Pass the page's
+/// `next_cursor` back to get the next one; `limit` 0 uses the default size.
 #[server(GetCommentsByPost, "/api")]
-pub async fn get_comments_by_post(post_id: String) -> Result<Vec<Comment>, ServerFnError> {
+pub async fn get_comments_by_post(
+    post_id: String,
+    status: Option<u8>,
+    sort: CommentSort,
+    cursor: Option<String>,
+    limit: u32,
+) -> Result<CommentPage, ServerFnError<CommentError>> {
+    use crate::server::{comment_crud, store::Listing};
+
+    let listing = Listing::Post { post_id, status, top_level: false };
+    comment_crud::get_comment_page(&listing, sort, cursor.as_deref(), limit)
+        .await
+        .map_err(fail)
+        .map(CommentPage::public)
+}
+
+/// One page of a post's top-level comments in `sort` order, each with its
+/// replies (oldest first) and reply counts. `status` filters the top-level
+/// comments only.
+#[server(GetThreads, "/api")]
+pub async fn get_threads(
+    post_id: String,
+    status: Option<u8>,
+    sort: CommentSort,
+    cursor: Option<String>,
+    limit: u32,
+) -> Result<ThreadPage, ServerFnError<CommentError>> {
+    use crate::server::comment_crud;
+
+    comment_crud::get_thread_page(&post_id, status, sort, cursor.as_deref(), limit)
+        .await
+        .map_err(fail)
+        .map(ThreadPage::public)
+}
+
+/// The comment `id` with all its replies and reply counts.
+#[server(GetThread, "/api")]
+pub async fn get_thread(id: String) -> Result<CommentThread, ServerFnError<CommentError>> {
     use crate::server::comment_crud;
 
-    comment_crud::get_comments_by_post(&post_id)
+    comment_crud::get_thread(&id)
         .await
-        .map_err(|e| ServerFnError::new(e))
+        .map_err(fail)
+        .map(CommentThread::public)
 }
 
-/// Get a single comment by ID.
+/// Get a single comment by ID; `None` if it was deleted or hidden.
 #[server(GetComment, "/api")]
-pub async fn get_comment(id: String) -> Result<Option<Comment>, ServerFnError> {
+pub async fn get_comment(id: String) -> Result<Option<Comment>, ServerFnError<CommentError>> {
     use crate::server::comment_crud;
 
-    comment_crud::get_comment(&id)
+    comment_crud::get_visible_comment(&id)
         .await
-        .map_err(|e| ServerFnError::new(e))
+        .map_err(fail)
+        .map(|c| c.map(Comment::public))
 }
 
 /// Update a comment's content.
 #[server(UpdateComment, "/api")]
-pub async fn update_comment(id: String, content: String) -> Result<Comment, ServerFnError> {
+pub async fn update_comment(id: String, content: String) -> Result<Comment, ServerFnError<CommentError>> {
     use crate::server::comment_crud;
 
     comment_crud::update_comment(&id, &content)
         .await
-        .map_err(|e| ServerFnError::new(e))
+        .map_err(fail)
+        .map(Comment::public)
 }
 
 /// Like a comment.
 #[server(LikeComment, "/api")]
-pub async fn like_comment(id: String) -> Result<Comment, ServerFnError> {
+pub async fn like_comment(id: String) -> Result<Comment, ServerFnError<CommentError>> {
     use crate::server::comment_crud;
 
     comment_crud::like_comment(&id)
         .await
-        .map_err(|e| ServerFnError::new(e))
+        .map_err(fail)
+        .map(Comment::public)
 }
 
 /// Soft-delete a comment.
 #[server(DeleteComment, "/api")]
-pub async fn delete_comment(id: String) -> Result<(), ServerFnError> {
+pub async fn delete_comment(id: String) -> Result<(), ServerFnError<CommentError>> {
     use crate::server::comment_crud;
 
     comment_crud::soft_delete_comment(&id)
         .await
-        .map_err(|e| ServerFnError::new(e))
+        .map_err(fail)
 }
 
-/// Get all comments (admin).
+/// One page of every comment, deleted ones included (admin).
 #[server(GetAllComments, "/api")]
-pub async fn get_all_comments() -> Result<Vec<Comment>, ServerFnError> {
-    use crate::server::comment_crud;
+pub async fn get_all_comments(
+    admin_token: String,
+    sort: CommentSort,
+    cursor: Option<String>,
+    limit: u32,
+) -> Result<CommentPage, ServerFnError<CommentError>> {
+    use crate::server::{admin, comment_crud, store::Listing};
 
-    comment_crud::get_all_comments()
+    admin::require_admin(&admin_token).map_err(fail)?;
+    comment_crud::get_comment_page(&Listing::All, sort, cursor.as_deref(), limit)
         .await
-        .map_err(|e| ServerFnError::new(e))
+        .map_err(fail)
 }
 
-/// Get negative comments for a post.
+/// One page of a post's negative comments.
 #[server(GetNegativeComments, "/api")]
-pub async fn get_negative_comments(post_id: String) -> Result<Vec<Comment>, ServerFnError> {
+pub async fn get_negative_comments(
+    post_id: String,
+    sort: CommentSort,
+    cursor: Option<String>,
+    limit: u32,
+) -> Result<CommentPage, ServerFnError<CommentError>> {
+    use crate::server::{comment_crud, store::Listing};
+
+    let listing = Listing::Post { post_id, status: Some(1), top_level: false };
+    comment_crud::get_comment_page(&listing, sort, cursor.as_deref(), limit)
+        .await
+        .map_err(fail)
+        .map(CommentPage::public)
+}
+
+/// Per-aspect sentiment counts for a post (dashboard).
+#[server(GetAspectSummary, "/api")]
+pub async fn get_aspect_summary(post_id: String) -> Result<Vec<AspectSummary>, ServerFnError<CommentError>> {
     use crate::server::comment_crud;
 
-    comment_crud::get_negative_comments(&post_id)
+    comment_crud::get_aspect_summary(&post_id)
+        .await
+        .map_err(fail)
+}
+
+/// Most frequent keyphrases per sentiment class for a post ("cold soup",
+/// 12 negative mentions), over the last `days` days (0 = all time).
+#[server(GetTopKeyphrases, "/api")]
+pub async fn get_top_keyphrases(post_id: String, days: u32, limit: u32) -> Result<TopKeyphrases, ServerFnError<CommentError>> {
+    use crate::server::comment_crud;
+
+    comment_crud::get_top_keyphrases(&post_id, days, limit)
+        .await
+        .map_err(fail)
+}
+
+/// Extractive summary of a post's reviews from the last `days` days
+/// (0 = all time): counts and up to `per_class` representative sentences
+/// per sentiment class.
+#[server(GetReviewSummary, "/api")]
+pub async fn get_review_summary(post_id: String, days: u32, per_class: u32) -> Result<ReviewSummary, ServerFnError<CommentError>> {
+    use crate::server::summary;
+
+    summary::get_summary(&post_id, days, per_class as usize)
+        .await
+        .map_err(fail)
+}
+
+/// Themes of a post's negative comments, largest first, as last computed
+/// by `dene-admin topics`.
+#[server(GetTopics, "/api")]
+pub async fn get_topics(post_id: String) -> Result<Vec<Topic>, ServerFnError<CommentError>> {
+    use crate::server::topics;
+
+    topics::get_topics(&post_id)
+        .await
+        .map_err(fail)
+}
+
+/// Start re-analyzing every stored comment in the background (admin).
+/// Poll `get_reanalysis_status` for progress.
+#[server(StartReanalysis, "/api")]
+pub async fn start_reanalysis(
+    admin_token: String,
+    force: bool,
+    resubmit_negative: bool,
+) -> Result<(), ServerFnError<CommentError>> {
+    use crate::server::{admin, reanalysis};
+
+    admin::require_admin(&admin_token).map_err(fail)?;
+    reanalysis::start(reanalysis::ReanalysisOptions { force, resubmit_negative, ..Default::default() })
+        .map_err(fail)
+}
+
+/// Progress of the current or last re-analysis run (admin).
+#[server(GetReanalysisStatus, "/api")]
+pub async fn get_reanalysis_status(admin_token: String) -> Result<Option<ReanalysisReport>, ServerFnError<CommentError>> {
+    use crate::server::{admin, reanalysis};
+
+    admin::require_admin(&admin_token).map_err(fail)?;
+    Ok(reanalysis::status())
+}
+
+/// Comments queued for moderation or hidden as toxic (admin).
+#[server(GetModerationQueue, "/api")]
+pub async fn get_moderation_queue(admin_token: String) -> Result<Vec<Comment>, ServerFnError<CommentError>> {
+    use crate::server::{admin, comment_crud};
+
+    admin::require_admin(&admin_token).map_err(fail)?;
+    comment_crud::get_moderation_queue()
+        .await
+        .map_err(fail)
+}
+
+/// Approve (`Visible`), keep queued or hide a comment (admin).
+#[server(ModerateComment, "/api")]
+pub async fn moderate_comment(
+    admin_token: String,
+    id: String,
+    moderation: ModerationStatus,
+) -> Result<Comment, ServerFnError<CommentError>> {
+    use crate::server::{admin, comment_crud};
+
+    admin::require_admin(&admin_token).map_err(fail)?;
+    comment_crud::set_moderation(&id, moderation)
+        .await
+        .map_err(fail)
+}
+
+/// Comments whose sentiment the analyzer was unsure of, least confident
+/// first (admin).
+#[server(GetReviewQueue, "/api")]
+pub async fn get_review_queue(admin_token: String) -> Result<Vec<Comment>, ServerFnError<CommentError>> {
+    use crate::server::{admin, comment_crud};
+
+    admin::require_admin(&admin_token).map_err(fail)?;
+    comment_crud::get_review_queue()
+        .await
+        .map_err(fail)
+}
+
+/// Set the final sentiment label of a comment (admin): 1=negative,
+/// 2=neutral, 3=positive. It overrides the analyzer's label and is recorded
+/// under `moderator`'s name.
+#[server(LabelComment, "/api")]
+pub async fn label_comment(admin_token: String, id: String, status: u8, moderator: String) -> Result<Comment, ServerFnError<CommentError>> {
+    use crate::server::{admin, comment_crud};
+
+    admin::require_admin(&admin_token).map_err(fail)?;
+    comment_crud::set_human_label(&id, status, &moderator)
         .await
-        .map_err(|e| ServerFnError::new(e))
+        .map_err(fail)
 }
 
 /// Create a demo post_id for testing (returns a valid ObjectId string).
@@ -0,0 +1,288 @@
+//! Offline maintenance commands for the Dene server.
+//!
+//! ```text
+//! dene-admin train --input reviews.jsonl [--language en] [--output model.json] [--with-corrections]
+//! dene-admin eval [--analyzer keyword] [--corpus data/eval/reviews.jsonl]
+//! dene-admin reanalyze [--batch-size 100] [--force] [--resubmit-negative]
+//! dene-admin topics --post <post_id> [--topics <k>]
+//! dene-admin export-labels [--output labels.jsonl]
+//! dene-admin suggest-lexicon [--min-count 3]
+//! ```
+
+#[cfg(feature = "ssr")]
+mod admin {
+    use std::collections::HashMap;
+    use std::io::Write;
+    use std::path::PathBuf;
+    use dene::model::Comment;
+    use dene::server::comment_crud;
+    use dene::server::reanalysis::{self, ReanalysisOptions};
+    use dene::server::store;
+    use dene::server::topics;
+    use dene::server::sentiment::{self, bayes, corrections, eval, Language, SentimentAnalyzer};
+
+    const USAGE: &str = "\
+Usage: dene-admin <command> [options]
+
+Commands:
+  train --input <file.jsonl> [--language <code>] [--output <file.json>] [--with-corrections]
+      Train a Naive Bayes sentiment model from labeled reviews, one JSON
+      object per line: {\"text\": \"...\", \"label\": \"positive\", \"language\": \"en\"}.
+      \"label\" is negative, neutral or positive; \"language\" is detected when
+      missing. Without --language, one model is trained per language found
+      and written to $SENTIMENT_MODEL_DIR/<code>.json (default data/models).
+      --with-corrections adds the comments moderators labelled (--input is
+      then optional).
+
+  eval [--analyzer <name>] [--corpus <file.jsonl>] [--baseline <file.json>]
+      Run an analyzer (default: $SENTIMENT_ANALYZER, else keyword) over a
+      labeled corpus (default data/eval/reviews.jsonl) and print accuracy,
+      per-class precision/recall/F1, the confusion matrix and calibration.
+      Fails if accuracy is below the baseline (default data/eval/baseline.json).
+
+  reanalyze [--batch-size <n>] [--force] [--resubmit-negative]
+      Re-run sentiment analysis over all stored comments with the configured
+      analyzer, rewriting those scored by an older analyzer version (or all
+      of them with --force). --resubmit-negative sends comments that became
+      negative to the Soroban sampler.
+
+  topics --post <post_id> [--topics <k>]
+      Group the post's negative comments into at most k themes (default
+      about the square root of half their number, at most 12) and replace
+      its stored topics, each with a label and example comments.
+
+  export-labels [--output <file.jsonl>]
+      Write the comments moderators labelled as training examples, in the
+      format train and eval read (default: standard output).
+
+  suggest-lexicon [--min-count <n>]
+      List words missing from the lexicons that keep appearing in comments
+      moderators relabelled negative or positive (in at least n of them,
+      default 3), as [[terms]] entries to review and paste into a lexicon.";
+
+    /// Options that are switches and take no value.
+    const FLAGS: &[&str] = &["force", "resubmit-negative", "with-corrections"];
+
+    /// Split `--name value` pairs and `--flag` switches into a map.
+    fn parse_options(args: &[String]) -> Result<HashMap<String, String>, String> {
+        let mut options = HashMap::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let name = arg.strip_prefix("--").ok_or_else(|| format!("Unexpected argument: {}", arg))?;
+            let value = if FLAGS.contains(&name) {
+                "true".to_string()
+            } else {
+                args.next().ok_or_else(|| format!("Missing value for --{}", name))?.clone()
+            };
+            options.insert(name.to_string(), value);
+        }
+        Ok(options)
+    }
+
+    /// The comments moderators labelled, from the database.
+    fn labelled_comments() -> Result<Vec<Comment>, String> {
+        let runtime = tokio::runtime::Runtime::new().map_err(|e| format!("Cannot start runtime: {}", e))?;
+        runtime.block_on(async {
+            store::init_store().await?;
+            Ok(comment_crud::get_human_labelled_comments().await?)
+        })
+    }
+
+    fn train(options: &HashMap<String, String>) -> Result<(), String> {
+        let with_corrections = options.contains_key("with-corrections");
+        let input = options.get("input");
+        if input.is_none() && !with_corrections {
+            return Err("train needs --input <file.jsonl>".to_string());
+        }
+        let languages = match options.get("language") {
+            Some(code) => vec![Language::from_code(code).ok_or_else(|| format!("Unsupported language: {}", code))?],
+            None if options.contains_key("output") => return Err("--output needs --language".to_string()),
+            None => Language::ALL.to_vec(),
+        };
+
+        let mut examples = Vec::new();
+        if let Some(input) = input {
+            examples = bayes::read_examples(input.as_ref())?;
+            println!("Read {} examples from {}", examples.len(), input);
+        }
+        if with_corrections {
+            let labelled = corrections::examples(&labelled_comments()?);
+            println!("Read {} moderator labels", labelled.len());
+            examples.extend(labelled);
+        }
+
+        let dir = bayes::model_dir();
+        let mut trained = 0;
+        for language in languages {
+            let data: Vec<_> = examples.iter().filter(|e| e.language() == Some(language)).cloned().collect();
+            if data.is_empty() {
+                continue;
+            }
+
+            let model = bayes::train(language, &data)?;
+            let correct = data
+                .iter()
+                .filter(|e| e.label() == Some(model.analyze(&e.text).label))
+                .count();
+            let path = options.get("output").map_or_else(|| bayes::model_path(&dir, language), PathBuf::from);
+            model.save(&path)?;
+
+            println!(
+                "{}: {} documents, {} features, training accuracy {:.1}% -> {}",
+                language.code(),
+                model.documents(),
+                model.vocabulary(),
+                correct as f64 * 100.0 / data.len() as f64,
+                path.display()
+            );
+            trained += 1;
+        }
+
+        if trained == 0 {
+            return Err("No examples in a supported language".to_string());
+        }
+        Ok(())
+    }
+
+    fn evaluate(options: &HashMap<String, String>) -> Result<(), String> {
+        let analyzer = options
+            .get("analyzer")
+            .cloned()
+            .or_else(|| std::env::var("SENTIMENT_ANALYZER").ok())
+            .unwrap_or_else(|| "keyword".to_string());
+        let corpus = options.get("corpus").map_or(eval::CORPUS_PATH, String::as_str);
+        let baseline = options.get("baseline").map_or(eval::BASELINE_PATH, String::as_str);
+
+        let examples = bayes::read_examples(corpus.as_ref())?;
+        let report = eval::evaluate(analyzer.trim(), &examples)?;
+        println!("{}", report);
+        eval::check_baseline(&report, &eval::load_baseline(baseline.as_ref())?)
+    }
+
+    fn reanalyze(options: &HashMap<String, String>) -> Result<(), String> {
+        let batch_size = match options.get("batch-size") {
+            Some(n) => n.parse().map_err(|_| format!("Invalid --batch-size: {}", n))?,
+            None => reanalysis::DEFAULT_BATCH_SIZE,
+        };
+        let options = ReanalysisOptions {
+            batch_size,
+            force: options.contains_key("force"),
+            resubmit_negative: options.contains_key("resubmit-negative"),
+        };
+
+        let runtime = tokio::runtime::Runtime::new().map_err(|e| format!("Cannot start runtime: {}", e))?;
+        let report = runtime.block_on(async {
+            store::init_store().await?;
+            Ok::<_, String>(
+                reanalysis::run(options, |r| {
+                    if !r.finished {
+                        println!("{} scanned, {} updated, {} labels changed", r.scanned, r.updated, r.labels_changed);
+                    }
+                })
+                .await,
+            )
+        })?;
+
+        println!(
+            "Done: {} scanned, {} updated, {} labels changed, {} newly negative, {} resubmitted, {} errors",
+            report.scanned, report.updated, report.labels_changed, report.newly_negative, report.resubmitted, report.errors
+        );
+        match report.failure {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    fn build_topics(options: &HashMap<String, String>) -> Result<(), String> {
+        let post_id = options.get("post").ok_or("topics needs --post <post_id>")?;
+        let k = match options.get("topics") {
+            Some(n) => Some(n.parse().map_err(|_| format!("Invalid --topics: {}", n))?),
+            None => None,
+        };
+
+        let runtime = tokio::runtime::Runtime::new().map_err(|e| format!("Cannot start runtime: {}", e))?;
+        let topics = runtime.block_on(async {
+            store::init_store().await?;
+            Ok::<_, String>(topics::rebuild(post_id, k).await?)
+        })?;
+
+        for topic in &topics {
+            println!("{:>4}  {}", topic.size, topic.label);
+            for example in &topic.examples {
+                println!("        \"{}\"", example.content);
+            }
+        }
+        println!("Stored {} topics for {}", topics.len(), post_id);
+        Ok(())
+    }
+
+    fn export_labels(options: &HashMap<String, String>) -> Result<(), String> {
+        let examples = corrections::examples(&labelled_comments()?);
+        let mut out: Box<dyn Write> = match options.get("output") {
+            Some(path) => Box::new(std::fs::File::create(path).map_err(|e| format!("Cannot create {}: {}", path, e))?),
+            None => Box::new(std::io::stdout().lock()),
+        };
+        for example in &examples {
+            let line = serde_json::to_string(example).map_err(|e| format!("Cannot encode example: {}", e))?;
+            writeln!(out, "{}", line).map_err(|e| format!("Write error: {}", e))?;
+        }
+        if let Some(path) = options.get("output") {
+            println!("Exported {} labels to {}", examples.len(), path);
+        }
+        Ok(())
+    }
+
+    fn suggest_lexicon(options: &HashMap<String, String>) -> Result<(), String> {
+        let min_count = match options.get("min-count") {
+            Some(n) => n.parse().map_err(|_| format!("Invalid --min-count: {}", n))?,
+            None => 3,
+        };
+
+        let suggestions = corrections::suggest_terms(&labelled_comments()?, min_count);
+        for suggestion in &suggestions {
+            println!("# {}, {} corrections", suggestion.language.code(), suggestion.corrections);
+            println!("[[terms]]");
+            println!("word = \"{}\"", suggestion.word);
+            println!("polarity = \"{}\"\n", suggestion.label.as_str());
+        }
+        println!("# {} suggestions", suggestions.len());
+        Ok(())
+    }
+
+    pub fn run(args: &[String]) -> Result<(), String> {
+        // Use the same lexicons and analyzer the server would load.
+        sentiment::init_analyzer()?;
+
+        match args.split_first() {
+            Some((command, rest)) if command == "train" => train(&parse_options(rest)?),
+            Some((command, rest)) if command == "eval" => evaluate(&parse_options(rest)?),
+            Some((command, rest)) if command == "reanalyze" => reanalyze(&parse_options(rest)?),
+            Some((command, rest)) if command == "topics" => build_topics(&parse_options(rest)?),
+            Some((command, rest)) if command == "export-labels" => export_labels(&parse_options(rest)?),
+            Some((command, rest)) if command == "suggest-lexicon" => suggest_lexicon(&parse_options(rest)?),
+            Some((command, _)) if command != "help" && command != "--help" => {
+                Err(format!("Unknown command: {}\n\n{}", command, USAGE))
+            }
+            _ => {
+                println!("{}", USAGE);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(feature = "ssr")]
+fn main() {
+    dotenv::dotenv().ok();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Err(e) = admin::run(&args) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(not(feature = "ssr"))]
+fn main() {
+    // The admin commands need the server-side code (`--features ssr`).
+}
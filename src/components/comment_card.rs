@@ -1,16 +1,35 @@
 use leptos::prelude::*;
-use crate::model::Comment;
+use crate::model::{Comment, MAX_DEPTH};
 
 #[component]
 pub fn CommentCard(
     comment: Comment,
+    /// Replies at every level below this comment.
+    reply_count: u32,
     on_like: Callback<String>,
     on_delete: Callback<String>,
+    /// Called with this comment's id and the reply's text.
+    on_reply: Callback<(String, String)>,
     current_user: ReadSignal<String>,
 ) -> impl IntoView {
     let comment_id = comment.id.clone().unwrap_or_default();
     let comment_id_like = comment_id.clone();
     let comment_id_delete = comment_id.clone();
+    let comment_id_reply = comment_id.clone();
+
+    // Replies can be written by any connected wallet, down to `MAX_DEPTH`
+    let can_reply = comment.depth < MAX_DEPTH;
+    let is_connected = move || !current_user.get().is_empty();
+    let (replying, set_replying) = signal(false);
+    let (reply, set_reply) = signal(String::new());
+    let handle_reply = move |ev: web_sys::SubmitEvent| {
+        ev.prevent_default();
+        let text = reply.get();
+        if text.trim().is_empty() { return; }
+        on_reply.run((comment_id_reply.clone(), text));
+        set_reply.set(String::new());
+        set_replying.set(false);
+    };
 
     let sentiment_class = comment.sentiment_css_class().to_string();
     let sentiment_label = comment.sentiment_label().to_string();
@@ -28,7 +47,31 @@ pub fn CommentCard(
     let author_key = comment.author_public_key.clone();
     let is_own_comment = move || current_user.get() == author_key;
 
-    let depth_class = format!("comment-depth-{}", comment.depth.min(4));
+    let language = comment.language.clone();
+
+    // Explain the verdict: a tooltip on the badge, highlighted terms and
+    // underlined negative sentences
+    let evidence_summary = Some(comment.evidence_summary()).filter(|s| !s.is_empty());
+    let content = comment
+        .content_segments()
+        .into_iter()
+        .map(|segment| {
+            let sentence_class = if segment.negative_sentence { " sentence-negative" } else { "" };
+            match segment.weight {
+                Some(weight) => {
+                    let term_class = if weight < 0.0 { "sentiment-term sentiment-term-negative" } else { "sentiment-term sentiment-term-positive" };
+                    let class = format!("{}{}", term_class, sentence_class);
+                    view! { <mark class=class title=format!("{:+.1}", weight)>{segment.text}</mark> }.into_any()
+                }
+                None if segment.negative_sentence => {
+                    view! { <span class="sentence-negative">{segment.text}</span> }.into_any()
+                }
+                None => segment.text.into_any(),
+            }
+        })
+        .collect_view();
+
+    let depth_class = format!("comment-depth-{}", comment.depth.min(MAX_DEPTH));
     let time_ago = format_time_ago(comment.created_at);
 
     view! {
@@ -39,7 +82,8 @@ pub fn CommentCard(
                     <span class="author-key">{author_short}</span>
                 </div>
                 <div class="comment-meta">
-                    <span class={format!("sentiment-badge sentiment-{}", sentiment_label)}>
+                    {language.map(|lang| view! { <span class="language-badge">{lang}</span> })}
+                    <span class={format!("sentiment-badge sentiment-{}", sentiment_label)} title=evidence_summary>
                         {sentiment_label.clone()}
                     </span>
                     <span class="comment-time">{time_ago}</span>
@@ -47,7 +91,7 @@ pub fn CommentCard(
             </div>
 
             <div class="comment-body">
-                <p class="comment-content">{comment.content.clone()}</p>
+                <p class="comment-content">{content}</p>
             </div>
 
             <div class="comment-actions">
@@ -80,11 +124,47 @@ pub fn CommentCard(
                     }
                 }
 
+                <Show when=move || can_reply && is_connected()>
+                    <button
+                        class="action-btn reply-btn"
+                        on:click=move |_| set_replying.update(|r| *r = !*r)
+                    >
+                        <span class="action-icon">"↩"</span>
+                        " Reply"
+                    </button>
+                </Show>
+
+                {(reply_count > 0).then(|| view! {
+                    <span class="reply-count">
+                        {format!("{} {}", reply_count, if reply_count == 1 { "reply" } else { "replies" })}
+                    </span>
+                })}
+
                 <div class="comment-score">
                     <span class="score-label">"Score:"</span>
                     <span class="score-value">{comment.scoring}</span>
                 </div>
             </div>
+
+            <Show when=move || replying.get() && is_connected()>
+                <form class="reply-form" on:submit=handle_reply.clone()>
+                    <textarea
+                        class="comment-textarea reply-textarea"
+                        placeholder="Write a reply..."
+                        prop:value=reply
+                        on:input=move |ev| set_reply.set(event_target_value(&ev))
+                        rows=2
+                        maxlength=500
+                    ></textarea>
+                    <button
+                        type="submit"
+                        class="submit-btn"
+                        disabled=move || reply.get().trim().is_empty()
+                    >
+                        "Reply"
+                    </button>
+                </form>
+            </Show>
         </div>
     }
 }
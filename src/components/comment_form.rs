@@ -29,7 +29,7 @@ pub fn CommentForm(
         <div class="comment-form-container">
             <h3 class="form-title">"Share Your Experience"</h3>
             {
-                let is_conn = is_connected.clone();
+                let is_conn = is_connected;
                 let handle = handle_submit.clone();
                 move || {
                     if is_conn() {
@@ -8,20 +8,6 @@ pub fn Navbar(
 ) -> impl IntoView {
     let (mobile_menu_open, set_mobile_menu_open) = signal(false);
 
-    let wallet_display = move || {
-        let state = wallet_state.get();
-        if state.connected {
-            let key = state.public_key.clone();
-            if key.len() > 10 {
-                format!("{}...{}", &key[..4], &key[key.len()-4..])
-            } else {
-                key
-            }
-        } else {
-            String::new()
-        }
-    };
-
     let beans_display = move || {
         let state = wallet_state.get();
         if state.connected {
@@ -123,7 +123,7 @@ pub fn WalletButton(
                 } else {
                     let conn = connecting.get();
                     leptos::either::Either::Right(view! {
-                        <button class="wallet-btn wallet-btn-connect" on:click=do_connect.clone()>
+                        <button class="wallet-btn wallet-btn-connect" on:click=do_connect>
                             <span class="wallet-icon">"🔗"</span>
                             {if conn { " Connecting..." } else { " Connect Freighter" }}
                         </button>
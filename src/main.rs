@@ -16,11 +16,15 @@ async fn main() -> std::io::Result<()> {
         .init()
         .expect("Failed to init logger");
 
-    // Initialize MongoDB (graceful — app starts even if DB is down)
-    match dene::server::db::init_db().await {
-        Ok(()) => log::info!("✅ MongoDB initialized"),
-        Err(e) => log::warn!("⚠️  MongoDB init error (app will start, DB ops may fail): {}", e),
-    }
+    // Select the sentiment analyzer before any request can reach the CRUD layer
+    dene::server::sentiment::init_analyzer().expect("Failed to init sentiment analyzer");
+    dene::server::sentiment::lexicon::spawn_reload_watcher();
+    dene::server::review::init_review().expect("Failed to init sentiment review");
+    dene::server::toxicity::init_toxicity().expect("Failed to init toxicity classifier");
+    dene::server::redaction::init_redaction().expect("Failed to init PII redaction");
+
+    // Open the comment store (MongoDB is graceful — app starts even if the DB is down)
+    dene::server::store::init_store().await.expect("Failed to init comment store");
 
     let conf = get_configuration(None).unwrap();
     let addr = conf.leptos_options.site_addr;
@@ -38,7 +42,7 @@ async fn main() -> std::io::Result<()> {
         actix_web::App::new()
             // Static files MUST come before leptos_routes so /pkg/* is served correctly
             .service(Files::new("/pkg", format!("{}/pkg", site_root_str)))
-            .service(Files::new("/assets", format!("{}", site_root_str)))
+            .service(Files::new("/assets", site_root_str.clone()))
             .leptos_routes(routes.clone(), {
                 let leptos_options = leptos_options.clone();
                 move || {
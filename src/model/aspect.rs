@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+/// Part of the dining experience a review talks about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Aspect {
+    Food,
+    Service,
+    Price,
+    Ambience,
+    Hygiene,
+}
+
+impl Aspect {
+    pub const ALL: [Aspect; 5] = [Aspect::Food, Aspect::Service, Aspect::Price, Aspect::Ambience, Aspect::Hygiene];
+
+    /// Lowercase name, as used in lexicon files and stored on comments.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Aspect::Food => "food",
+            Aspect::Service => "service",
+            Aspect::Price => "price",
+            Aspect::Ambience => "ambience",
+            Aspect::Hygiene => "hygiene",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Aspect::ALL.into_iter().find(|a| a.as_str() == name)
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Aspect::Food => "Food",
+            Aspect::Service => "Service",
+            Aspect::Price => "Price",
+            Aspect::Ambience => "Ambience",
+            Aspect::Hygiene => "Hygiene",
+        }
+    }
+}
+
+/// Sentiment of one comment towards one aspect.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AspectSentiment {
+    pub aspect: Aspect,
+    /// 1=negative, 2=neutral, 3=positive
+    pub status: u8,
+    /// Net weight of the lexicon hits attributed to the aspect.
+    pub weight: f64,
+}
+
+/// How many comments of a post were negative/neutral/positive about an aspect.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AspectSummary {
+    pub aspect: Aspect,
+    pub negative: u32,
+    pub neutral: u32,
+    pub positive: u32,
+}
+
+impl AspectSummary {
+    pub fn mentions(&self) -> u32 {
+        self.negative + self.neutral + self.positive
+    }
+}
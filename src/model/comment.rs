@@ -1,4 +1,71 @@
 use serde::{Deserialize, Serialize};
+use super::{AspectSentiment, Keyphrase, ModerationStatus, ToxicTerm};
+
+/// A word or phrase that moved the sentiment verdict, with its byte range
+/// in `Comment::content`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SentimentTerm {
+    pub term: String,
+    /// Negative pulls towards negative, positive towards positive.
+    pub weight: f64,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Characters of a comment recorded on-chain.
+pub const ONCHAIN_EXCERPT_CHARS: usize = 32;
+
+/// The part of `content` recorded on-chain: the first
+/// `ONCHAIN_EXCERPT_CHARS` characters, with "..." if cut. Pass only
+/// redacted text.
+pub fn onchain_excerpt(content: &str) -> String {
+    match content.char_indices().nth(ONCHAIN_EXCERPT_CHARS) {
+        Some((cut, _)) => format!("{}...", &content[..cut]),
+        None => content.to_string(),
+    }
+}
+
+/// Sentiment of one sentence of `Comment::content`, by byte range.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SentenceSentiment {
+    pub start: usize,
+    pub end: usize,
+    /// 1=negative, 2=neutral, 3=positive
+    pub status: u8,
+    pub scoring: u8,
+}
+
+/// A moderator's change of a comment's sentiment label.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LabelOverride {
+    /// Who made the change, as they signed it.
+    pub moderator: String,
+    /// Milliseconds since epoch
+    pub at: i64,
+    /// `status` before and after: 1=negative, 2=neutral, 3=positive
+    pub old_status: u8,
+    pub new_status: u8,
+}
+
+/// A comment just posted, as returned to its author.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostedComment {
+    /// Without internal fields, see `Comment::public`.
+    pub comment: Comment,
+    /// `Comment::is_negative_sample()`, judged before the toxic terms were
+    /// stripped: the author's wallet then records the comment on-chain.
+    pub negative_sample: bool,
+}
+
+/// A run of `Comment::content` for display.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContentSegment {
+    pub text: String,
+    /// Weight of the evidence term this run is, if any.
+    pub weight: Option<f64>,
+    /// The run lies in a sentence judged negative.
+    pub negative_sentence: bool,
+}
 
 /// Comment model matching the MongoDB collection schema.
 /// Timestamps are stored as i64 (milliseconds since epoch) for cross-compilation
@@ -10,15 +77,71 @@ pub struct Comment {
 
     pub post_id: String,
     pub author_public_key: String,
+    /// Review text with emails, phone numbers, IBANs and card numbers
+    /// masked ("[email]").
     pub content: String,
+    /// Personal data was masked in `content`.
+    #[serde(default)]
+    pub redacted: bool,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub parent_id: Option<String>,
 
     pub depth: u8,
-    /// Sentiment status: 1=negative, 2=neutral, 3=positive
+    /// Sentiment status: 1=negative, 2=neutral, 3=positive. The
+    /// moderator's `human_status` when set, else `auto_status`.
     pub status: u8,
     pub scoring: u8,
+    /// Label the analyzer gave.
+    #[serde(default)]
+    pub auto_status: u8,
+    /// Label a moderator set; takes precedence over `auto_status` until
+    /// the content is edited.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub human_status: Option<u8>,
+    /// 0.0–1.0, how sure the analyzer is of `auto_status`.
+    #[serde(default)]
+    pub confidence: f32,
+    /// The analyzer was unsure and no moderator has labelled it yet.
+    #[serde(default)]
+    pub needs_review: bool,
+    /// Every label change made by a moderator, oldest first.
+    #[serde(default)]
+    pub label_overrides: Vec<LabelOverride>,
+    /// Detected language code ("en", "tr", "fr", "de"); `None` if unrecognised.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// The language could not be analyzed, so `status`/`scoring` are a
+    /// neutral placeholder rather than a verdict.
+    #[serde(default)]
+    pub unanalyzed: bool,
+    /// Analyzer and lexicon/model version that produced `status`/`scoring`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub analyzer_version: Option<String>,
+    /// Per-aspect sentiment (food, service, ...) for the aspects mentioned.
+    #[serde(default)]
+    pub aspects: Vec<AspectSentiment>,
+    /// Terms that drove the verdict, in text order.
+    #[serde(default)]
+    pub evidence: Vec<SentimentTerm>,
+    /// Per-sentence sentiment, in text order; `status` combines them.
+    #[serde(default)]
+    pub sentences: Vec<SentenceSentiment>,
+    /// Keyphrases ("cold soup") with the sentiment of their sentence.
+    #[serde(default)]
+    pub keyphrases: Vec<Keyphrase>,
+    /// Abuse score, 0–100, independent of sentiment.
+    #[serde(default)]
+    pub toxicity: u8,
+    /// Abusive terms found, in text order.
+    #[serde(default)]
+    pub toxic_terms: Vec<ToxicTerm>,
+    #[serde(default)]
+    pub moderation: ModerationStatus,
+    /// Spam likelihood (links, phone numbers, near-duplicates, bursts),
+    /// 0–100, measured when the comment was posted or last edited.
+    #[serde(default)]
+    pub spam_score: u8,
     pub likes_count: i32,
     pub is_deleted: bool,
     /// Milliseconds since epoch
@@ -29,6 +152,9 @@ pub struct Comment {
 
 impl Comment {
     pub fn sentiment_label(&self) -> &'static str {
+        if self.unanalyzed {
+            return "unanalyzed";
+        }
         match self.status {
             1 => "negative",
             3 => "positive",
@@ -36,6 +162,91 @@ impl Comment {
         }
     }
 
+    /// Split `content` into plain runs and evidence terms (with their
+    /// weight), further split at the edges of negative sentences, for
+    /// highlighting. Spans that overlap or do not fall on character
+    /// boundaries are skipped.
+    pub fn content_segments(&self) -> Vec<ContentSegment> {
+        let mut terms: Vec<&SentimentTerm> = self.evidence.iter().collect();
+        terms.sort_by_key(|t| t.start);
+
+        let mut runs: Vec<(usize, usize, Option<f64>)> = Vec::new();
+        let mut pos = 0;
+        for term in terms {
+            let valid = self.content.get(pos..term.start).is_some() && self.content.get(term.start..term.end).is_some();
+            if term.start < pos || term.start >= term.end || !valid {
+                continue;
+            }
+            if pos < term.start {
+                runs.push((pos, term.start, None));
+            }
+            runs.push((term.start, term.end, Some(term.weight)));
+            pos = term.end;
+        }
+        if pos < self.content.len() {
+            runs.push((pos, self.content.len(), None));
+        }
+
+        let negative: Vec<(usize, usize)> = self
+            .sentences
+            .iter()
+            .filter(|s| s.status == 1 && s.start < s.end && self.content.get(s.start..s.end).is_some())
+            .map(|s| (s.start, s.end))
+            .collect();
+        let mut cuts: Vec<usize> = negative.iter().flat_map(|(start, end)| [*start, *end]).collect();
+        cuts.sort_unstable();
+
+        let mut segments = Vec::new();
+        for (start, end, weight) in runs {
+            let inner = cuts.iter().copied().filter(|c| *c > start && *c < end);
+            let bounds: Vec<usize> = std::iter::once(start).chain(inner).chain([end]).collect();
+            for pair in bounds.windows(2) {
+                let (from, to) = (pair[0], pair[1]);
+                segments.push(ContentSegment {
+                    text: self.content[from..to].to_string(),
+                    weight,
+                    negative_sentence: negative.iter().any(|(s, e)| *s <= from && to <= *e),
+                });
+            }
+        }
+        segments
+    }
+
+    /// One-line explanation of the verdict, e.g. `rude −1.5, fresh +1.0`.
+    pub fn evidence_summary(&self) -> String {
+        self.evidence
+            .iter()
+            .map(|t| format!("{} {}{:.1}", t.term, if t.weight < 0.0 { "−" } else { "+" }, t.weight.abs()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Whether the comment belongs in the on-chain negative sampler: it is
+    /// negative, visible, and stays negative once evidence overlapping
+    /// abusive terms is discounted, so "I hate you, idiot" is left out. A
+    /// moderator's negative label stands on its own.
+    pub fn is_negative_sample(&self) -> bool {
+        if self.status != 1 || self.unanalyzed || self.moderation != ModerationStatus::Visible {
+            return false;
+        }
+        if self.toxic_terms.is_empty() || self.human_status == Some(1) {
+            return true;
+        }
+        let overlaps_abuse = |term: &SentimentTerm| self.toxic_terms.iter().any(|t| t.start < term.end && term.start < t.end);
+        let net: f64 = self.evidence.iter().filter(|t| !overlaps_abuse(t)).map(|t| t.weight).sum();
+        net < 0.0
+    }
+
+    /// The comment as sent to anyone but an admin: moderator names, the
+    /// abusive terms found and the spam score stay on the server. Without
+    /// `toxic_terms`, `is_negative_sample()` must be asked before this.
+    pub fn public(mut self) -> Self {
+        self.label_overrides.clear();
+        self.toxic_terms.clear();
+        self.spam_score = 0;
+        self
+    }
+
     pub fn sentiment_css_class(&self) -> &'static str {
         match self.status {
             1 => "comment-negative",
@@ -44,3 +255,133 @@ impl Comment {
         }
     }
 }
+
+#[cfg(test)]
+impl Comment {
+    /// A neutral, visible top-level comment on post "p" by "G", for tests
+    /// to adjust with struct update syntax.
+    pub(crate) fn sample(content: &str) -> Comment {
+        Comment {
+            id: None,
+            post_id: "p".to_string(),
+            author_public_key: "G".to_string(),
+            content: content.to_string(),
+            redacted: false,
+            parent_id: None,
+            depth: 0,
+            status: 2,
+            scoring: 50,
+            auto_status: 2,
+            human_status: None,
+            confidence: 1.0,
+            needs_review: false,
+            label_overrides: Vec::new(),
+            language: None,
+            unanalyzed: false,
+            analyzer_version: None,
+            aspects: Vec::new(),
+            evidence: Vec::new(),
+            sentences: Vec::new(),
+            keyphrases: Vec::new(),
+            toxicity: 0,
+            toxic_terms: Vec::new(),
+            moderation: ModerationStatus::Visible,
+            spam_score: 0,
+            likes_count: 0,
+            is_deleted: false,
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ToxicityCategory;
+
+    fn comment(content: &str, evidence: Vec<SentimentTerm>) -> Comment {
+        Comment { evidence, ..Comment::sample(content) }
+    }
+
+    fn term(term: &str, weight: f64, start: usize, end: usize) -> SentimentTerm {
+        SentimentTerm { term: term.to_string(), weight, start, end }
+    }
+
+    fn segment(text: &str, weight: Option<f64>, negative_sentence: bool) -> ContentSegment {
+        ContentSegment { text: text.to_string(), weight, negative_sentence }
+    }
+
+    #[test]
+    fn test_content_segments() {
+        let c = comment("Crème was great, staff rude", vec![term("rude", -1.5, 24, 28), term("great", 1.0, 11, 16)]);
+        assert_eq!(c.content_segments(), vec![
+            segment("Crème was ", None, false),
+            segment("great", Some(1.0), false),
+            segment(", staff ", None, false),
+            segment("rude", Some(-1.5), false),
+        ]);
+        assert_eq!(c.evidence_summary(), "rude −1.5, great +1.0");
+    }
+
+    #[test]
+    fn test_content_segments_mark_negative_sentences() {
+        let mut c = comment("Great view. The soup was cold.", vec![term("great", 1.0, 0, 5), term("cold", -1.0, 25, 29)]);
+        c.sentences = vec![
+            SentenceSentiment { start: 0, end: 11, status: 3, scoring: 75 },
+            SentenceSentiment { start: 12, end: 30, status: 1, scoring: 30 },
+        ];
+        assert_eq!(c.content_segments(), vec![
+            segment("Great", Some(1.0), false),
+            segment(" view. ", None, false),
+            segment("The soup was ", None, true),
+            segment("cold", Some(-1.0), true),
+            segment(".", None, true),
+        ]);
+    }
+
+    #[test]
+    fn test_negative_sample_skips_abuse() {
+        let mut c = comment("I hate you, idiot", vec![term("hate", -1.0, 2, 6)]);
+        c.status = 1;
+        assert!(c.is_negative_sample());
+
+        c.toxic_terms = vec![
+            ToxicTerm { term: "hate you".to_string(), category: ToxicityCategory::Attack, start: 2, end: 10 },
+            ToxicTerm { term: "idiot".to_string(), category: ToxicityCategory::Attack, start: 12, end: 17 },
+        ];
+        assert!(!c.is_negative_sample());
+
+        let mut c = comment("Cold soup, shit service", vec![term("cold", -1.0, 0, 4)]);
+        c.status = 1;
+        c.toxic_terms = vec![ToxicTerm { term: "shit".to_string(), category: ToxicityCategory::Profanity, start: 11, end: 15 }];
+        assert!(c.is_negative_sample());
+        c.moderation = ModerationStatus::Queued;
+        assert!(!c.is_negative_sample());
+    }
+
+    #[test]
+    fn test_public_hides_internal_fields() {
+        let mut c = comment("Cold soup, shit service", vec![term("cold", -1.0, 0, 4)]);
+        c.toxic_terms = vec![ToxicTerm { term: "shit".to_string(), category: ToxicityCategory::Profanity, start: 11, end: 15 }];
+        c.label_overrides = vec![LabelOverride { moderator: "ayse".to_string(), at: 0, old_status: 2, new_status: 1 }];
+        c.spam_score = 40;
+        let public = c.public();
+        assert!(public.toxic_terms.is_empty() && public.label_overrides.is_empty());
+        assert_eq!((public.spam_score, public.evidence.len()), (0, 1));
+    }
+
+    #[test]
+    fn test_onchain_excerpt() {
+        assert_eq!(onchain_excerpt("Cold soup"), "Cold soup");
+        let long = "é".repeat(40);
+        assert_eq!(onchain_excerpt(&long), format!("{}...", "é".repeat(32)));
+    }
+
+    #[test]
+    fn test_content_segments_skip_bad_spans() {
+        // 1..3 splits "è"; 20..30 is past the end
+        let c = comment("Crème", vec![term("x", 1.0, 1, 3), term("y", 1.0, 20, 30)]);
+        assert_eq!(c.content_segments(), vec![segment("Crème", None, false)]);
+    }
+}
@@ -0,0 +1,152 @@
+use std::fmt;
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+
+/// Why a comment operation failed. Returned by the server layer and carried
+/// across the server-function boundary, so the UI can tell the cases apart.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "message", rename_all = "snake_case")]
+pub enum CommentError {
+    NotFound(String),
+    /// An id that is not an ObjectId hex string.
+    InvalidId(String),
+    /// Input that breaks a rule: empty moderator name, unknown label, spam.
+    Validation(String),
+    /// Missing or wrong admin token.
+    Forbidden(String),
+    /// The operation clashes with one in progress.
+    Conflict(String),
+    /// The backend failed; retrying may help.
+    Storage(String),
+    /// Too many comments in too short a time.
+    RateLimited(String),
+    /// The Soroban contract could not be reached or refused the submission.
+    ChainSubmission(String),
+}
+
+impl CommentError {
+    pub const KINDS: [&'static str; 8] = [
+        "not_found",
+        "invalid_id",
+        "validation",
+        "forbidden",
+        "conflict",
+        "storage",
+        "rate_limited",
+        "chain_submission",
+    ];
+
+    /// Stable snake_case name, as serialized.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CommentError::NotFound(_) => "not_found",
+            CommentError::InvalidId(_) => "invalid_id",
+            CommentError::Validation(_) => "validation",
+            CommentError::Forbidden(_) => "forbidden",
+            CommentError::Conflict(_) => "conflict",
+            CommentError::Storage(_) => "storage",
+            CommentError::RateLimited(_) => "rate_limited",
+            CommentError::ChainSubmission(_) => "chain_submission",
+        }
+    }
+
+    fn from_kind(kind: &str, message: String) -> Option<Self> {
+        Some(match kind {
+            "not_found" => CommentError::NotFound(message),
+            "invalid_id" => CommentError::InvalidId(message),
+            "validation" => CommentError::Validation(message),
+            "forbidden" => CommentError::Forbidden(message),
+            "conflict" => CommentError::Conflict(message),
+            "storage" => CommentError::Storage(message),
+            "rate_limited" => CommentError::RateLimited(message),
+            "chain_submission" => CommentError::ChainSubmission(message),
+            _ => return None,
+        })
+    }
+
+    /// The detail, without the kind.
+    pub fn message(&self) -> &str {
+        match self {
+            CommentError::NotFound(m)
+            | CommentError::InvalidId(m)
+            | CommentError::Validation(m)
+            | CommentError::Forbidden(m)
+            | CommentError::Conflict(m)
+            | CommentError::Storage(m)
+            | CommentError::RateLimited(m)
+            | CommentError::ChainSubmission(m) => m,
+        }
+    }
+
+    /// HTTP status a server function answers with.
+    pub fn status_code(&self) -> u16 {
+        match self {
+            CommentError::NotFound(_) => 404,
+            CommentError::InvalidId(_) => 400,
+            CommentError::Validation(_) => 422,
+            CommentError::Forbidden(_) => 403,
+            CommentError::Conflict(_) => 409,
+            CommentError::Storage(_) => 503,
+            CommentError::RateLimited(_) => 429,
+            CommentError::ChainSubmission(_) => 502,
+        }
+    }
+
+    /// Text for people: the message where it is meant for the user, a
+    /// generic line where it is an internal detail.
+    pub fn user_message(&self) -> String {
+        match self {
+            CommentError::Storage(_) => "The comment service is unavailable, please try again later".to_string(),
+            CommentError::ChainSubmission(_) => "The comment could not be recorded on-chain".to_string(),
+            other => other.message().to_string(),
+        }
+    }
+}
+
+/// `kind: message`; [`FromStr`] reads it back, which is how server
+/// functions send the error to the client.
+impl fmt::Display for CommentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.kind(), self.message())
+    }
+}
+
+impl FromStr for CommentError {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, message) = s.split_once(": ").ok_or_else(|| format!("Not a comment error: {}", s))?;
+        CommentError::from_kind(kind, message.to_string()).ok_or_else(|| format!("Unknown comment error kind: {}", kind))
+    }
+}
+
+impl std::error::Error for CommentError {}
+
+/// For callers that still report errors as text (CLI, background jobs).
+impl From<CommentError> for String {
+    fn from(error: CommentError) -> Self {
+        error.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_round_trips() {
+        for kind in CommentError::KINDS {
+            let error = CommentError::from_kind(kind, "Comment not found: 65a1 (x)".to_string()).unwrap();
+            assert_eq!(error.kind(), kind);
+            assert_eq!(error.to_string().parse::<CommentError>(), Ok(error));
+        }
+        assert!("boom".parse::<CommentError>().is_err());
+        assert!("exploded: boom".parse::<CommentError>().is_err());
+    }
+
+    #[test]
+    fn test_serializes_kind_and_message() {
+        let json = serde_json::to_value(CommentError::RateLimited("Slow down".to_string())).unwrap();
+        assert_eq!(json, serde_json::json!({ "kind": "rate_limited", "message": "Slow down" }));
+    }
+}
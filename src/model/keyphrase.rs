@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+
+/// A keyphrase of a comment with the sentiment of the sentence it came
+/// from, so a mixed review's "cold soup" still counts as a complaint.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Keyphrase {
+    pub phrase: String,
+    /// 1=negative, 2=neutral, 3=positive
+    pub status: u8,
+}
+
+/// How many comments mentioned a phrase.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhraseCount {
+    pub phrase: String,
+    pub count: u32,
+}
+
+/// Most frequent keyphrases of a post per sentiment class, most frequent
+/// first.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TopKeyphrases {
+    /// Days looked back; 0 means all time.
+    pub days: u32,
+    pub negative: Vec<PhraseCount>,
+    pub neutral: Vec<PhraseCount>,
+    pub positive: Vec<PhraseCount>,
+}
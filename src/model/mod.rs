@@ -1,3 +1,21 @@
+pub mod aspect;
 pub mod comment;
+pub mod error;
+pub mod keyphrase;
+pub mod moderation;
+pub mod page;
+pub mod reanalysis;
+pub mod summary;
+pub mod thread;
+pub mod topic;
 
+pub use aspect::*;
 pub use comment::*;
+pub use error::*;
+pub use keyphrase::*;
+pub use moderation::*;
+pub use page::*;
+pub use reanalysis::*;
+pub use summary::*;
+pub use thread::*;
+pub use topic::*;
@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+
+/// Whether a comment is shown, waiting for a moderator, or hidden; ordered
+/// from least to most severe.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModerationStatus {
+    #[default]
+    Visible,
+    /// Shown, but listed for a moderator to approve or hide.
+    Queued,
+    /// Left out of public listings.
+    Hidden,
+}
+
+impl ModerationStatus {
+    pub const ALL: [ModerationStatus; 3] = [ModerationStatus::Visible, ModerationStatus::Queued, ModerationStatus::Hidden];
+
+    /// Lowercase name, as stored on comments.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ModerationStatus::Visible => "visible",
+            ModerationStatus::Queued => "queued",
+            ModerationStatus::Hidden => "hidden",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        ModerationStatus::ALL.into_iter().find(|s| s.as_str() == name)
+    }
+}
+
+/// Kind of abuse a toxic term is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToxicityCategory {
+    Profanity,
+    /// Name-calling aimed at a person.
+    Attack,
+    Threat,
+    Slur,
+}
+
+impl ToxicityCategory {
+    pub const ALL: [ToxicityCategory; 4] =
+        [ToxicityCategory::Profanity, ToxicityCategory::Attack, ToxicityCategory::Threat, ToxicityCategory::Slur];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ToxicityCategory::Profanity => "profanity",
+            ToxicityCategory::Attack => "attack",
+            ToxicityCategory::Threat => "threat",
+            ToxicityCategory::Slur => "slur",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        ToxicityCategory::ALL.into_iter().find(|c| c.as_str() == name)
+    }
+}
+
+/// An abusive word or phrase, with its byte range in `Comment::content`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToxicTerm {
+    pub term: String,
+    pub category: ToxicityCategory,
+    pub start: usize,
+    pub end: usize,
+}
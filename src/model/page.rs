@@ -0,0 +1,82 @@
+use std::cmp::Ordering;
+use serde::{Deserialize, Serialize};
+use super::Comment;
+
+/// Order of a comment listing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommentSort {
+    #[default]
+    Newest,
+    Oldest,
+    MostLiked,
+    /// Lowest sentiment score first.
+    MostNegative,
+}
+
+impl CommentSort {
+    pub const ALL: [CommentSort; 4] =
+        [CommentSort::Newest, CommentSort::Oldest, CommentSort::MostLiked, CommentSort::MostNegative];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CommentSort::Newest => "newest",
+            CommentSort::Oldest => "oldest",
+            CommentSort::MostLiked => "most_liked",
+            CommentSort::MostNegative => "most_negative",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        CommentSort::ALL.into_iter().find(|s| s.as_str() == name)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CommentSort::Newest => "Newest",
+            CommentSort::Oldest => "Oldest",
+            CommentSort::MostLiked => "Most liked",
+            CommentSort::MostNegative => "Most negative",
+        }
+    }
+
+    /// The value a comment is sorted by; ties are broken by id.
+    pub fn key(self, comment: &Comment) -> i64 {
+        match self {
+            CommentSort::Newest | CommentSort::Oldest => comment.created_at,
+            CommentSort::MostLiked => comment.likes_count as i64,
+            CommentSort::MostNegative => comment.scoring as i64,
+        }
+    }
+
+    /// Whether larger keys (and ids) come first.
+    pub fn descending(self) -> bool {
+        matches!(self, CommentSort::Newest | CommentSort::MostLiked)
+    }
+
+    /// Order of `(key, id)` pairs in this sort.
+    pub fn compare(self, a: (i64, &str), b: (i64, &str)) -> Ordering {
+        let order = a.cmp(&b);
+        if self.descending() {
+            order.reverse()
+        } else {
+            order
+        }
+    }
+}
+
+/// One page of a comment listing.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CommentPage {
+    pub comments: Vec<Comment>,
+    /// Pass back to get the next page; `None` on the last one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+impl CommentPage {
+    /// The page with every comment `Comment::public`.
+    pub fn public(self) -> Self {
+        CommentPage { comments: self.comments.into_iter().map(Comment::public).collect(), ..self }
+    }
+}
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+/// Progress and outcome of a bulk re-analysis run.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReanalysisReport {
+    /// Comments read so far.
+    pub scanned: u32,
+    /// Comments whose stored analysis was rewritten.
+    pub updated: u32,
+    /// Updated comments whose label (or unanalyzed flag) changed.
+    pub labels_changed: u32,
+    /// Live comments that were not negative before and are now.
+    pub newly_negative: u32,
+    /// Newly negative comments handed to the Soroban sampler.
+    pub resubmitted: u32,
+    /// Comments that could not be updated.
+    pub errors: u32,
+    pub finished: bool,
+    /// Set if the run stopped early.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure: Option<String>,
+}
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+/// A sentence quoted in a summary, with the comment it came from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SummarySentence {
+    pub comment_id: String,
+    pub text: String,
+}
+
+/// One sentiment class of a summary.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ClassSummary {
+    /// Comments with this label.
+    pub comments: u32,
+    /// Sentences with this label, in comments of any label.
+    pub sentences: u32,
+    /// The most representative of those sentences, most representative
+    /// first.
+    pub highlights: Vec<SummarySentence>,
+}
+
+/// Extractive summary of a post's reviews.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReviewSummary {
+    pub post_id: String,
+    /// Days looked back; 0 means all time.
+    pub days: u32,
+    /// Reviews summarized.
+    pub comments: u32,
+    pub negative: ClassSummary,
+    pub neutral: ClassSummary,
+    pub positive: ClassSummary,
+    /// Milliseconds since epoch.
+    pub generated_at: i64,
+}
+
+impl ReviewSummary {
+    /// The classes with their lowercase labels, negative first.
+    pub fn classes(&self) -> [(&'static str, &ClassSummary); 3] {
+        [("negative", &self.negative), ("neutral", &self.neutral), ("positive", &self.positive)]
+    }
+}
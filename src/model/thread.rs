@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use super::Comment;
+
+/// Deepest reply level: a top-level comment has depth 0, a reply to it 1.
+pub const MAX_DEPTH: u8 = 4;
+
+/// A comment with its replies, oldest first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommentThread {
+    pub comment: Comment,
+    pub replies: Vec<CommentThread>,
+    /// Replies at every level below this comment.
+    pub reply_count: u32,
+}
+
+impl CommentThread {
+    /// Threads of `roots`, with the `replies` (at any depth) hung under
+    /// their parents in the order given. Replies whose parent is not there
+    /// are left out.
+    pub fn build(roots: Vec<Comment>, replies: Vec<Comment>) -> Vec<CommentThread> {
+        let mut children: HashMap<String, Vec<Comment>> = HashMap::new();
+        for reply in replies {
+            if let Some(parent) = reply.parent_id.clone() {
+                children.entry(parent).or_default().push(reply);
+            }
+        }
+        roots.into_iter().map(|root| Self::grow(root, &mut children)).collect()
+    }
+
+    fn grow(comment: Comment, children: &mut HashMap<String, Vec<Comment>>) -> CommentThread {
+        let own = comment.id.as_ref().and_then(|id| children.remove(id)).unwrap_or_default();
+        let replies: Vec<CommentThread> = own.into_iter().map(|c| Self::grow(c, children)).collect();
+        let reply_count = replies.iter().map(|r| 1 + r.reply_count).sum();
+        CommentThread { comment, replies, reply_count }
+    }
+
+    /// Whether the comment with `id` is in this thread.
+    pub fn contains(&self, id: &str) -> bool {
+        self.comment.id.as_deref() == Some(id) || self.replies.iter().any(|r| r.contains(id))
+    }
+
+    /// The comment with `id` in this thread.
+    pub fn comment_mut(&mut self, id: &str) -> Option<&mut Comment> {
+        if self.comment.id.as_deref() == Some(id) {
+            return Some(&mut self.comment);
+        }
+        self.replies.iter_mut().find_map(|r| r.comment_mut(id))
+    }
+
+    /// The thread with every comment `Comment::public`.
+    pub fn public(self) -> Self {
+        CommentThread {
+            comment: self.comment.public(),
+            replies: self.replies.into_iter().map(CommentThread::public).collect(),
+            reply_count: self.reply_count,
+        }
+    }
+
+    /// Every comment of the thread with its reply count, each followed by
+    /// its replies (the order a thread is read in).
+    pub fn flatten(&self) -> Vec<(Comment, u32)> {
+        let mut entries = vec![(self.comment.clone(), self.reply_count)];
+        for reply in &self.replies {
+            entries.extend(reply.flatten());
+        }
+        entries
+    }
+}
+
+/// One page of a post's threads.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThreadPage {
+    pub threads: Vec<CommentThread>,
+    /// Pass back to get the next page; `None` on the last one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+impl ThreadPage {
+    /// The page with every comment `Comment::public`.
+    pub fn public(self) -> Self {
+        ThreadPage { threads: self.threads.into_iter().map(CommentThread::public).collect(), ..self }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comment(id: &str, parent_id: Option<&str>) -> Comment {
+        Comment { id: Some(id.to_string()), parent_id: parent_id.map(str::to_string), ..Comment::sample(id) }
+    }
+
+    #[test]
+    fn test_build_nests_replies_and_counts_them() {
+        let replies = vec![
+            comment("b", Some("a")),
+            comment("c", Some("b")),
+            comment("d", Some("a")),
+            comment("x", Some("gone")),
+        ];
+        let threads = CommentThread::build(vec![comment("a", None), comment("e", None)], replies);
+
+        assert_eq!((threads[0].reply_count, threads[0].replies.len(), threads[1].reply_count), (3, 2, 0));
+        let order: Vec<_> = threads[0].flatten().into_iter().map(|(c, n)| (c.content, n)).collect();
+        let expected = [("a", 3), ("b", 1), ("c", 0), ("d", 0)].map(|(c, n)| (c.to_string(), n));
+        assert_eq!(order, expected);
+        assert!(threads[0].contains("c") && !threads[0].contains("x"));
+    }
+}
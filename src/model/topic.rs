@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+/// A comment chosen to illustrate a topic.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TopicExample {
+    pub comment_id: String,
+    pub content: String,
+}
+
+/// A theme found among a post's negative comments by the offline
+/// clustering job (`dene-admin topics`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Topic {
+    pub post_id: String,
+    /// The few most characteristic terms, e.g. "cold soup, bread".
+    pub label: String,
+    /// Most characteristic terms first.
+    pub terms: Vec<String>,
+    /// Comments in the topic.
+    pub size: u32,
+    /// Comments closest to the topic's centre, closest first.
+    pub examples: Vec<TopicExample>,
+    /// When the job ran, in milliseconds since epoch.
+    pub generated_at: i64,
+}
@@ -1,104 +1,271 @@
 use leptos::prelude::*;
-use crate::model::Comment;
 use crate::api::*;
 use crate::components::comment_card::CommentCard;
 use crate::components::comment_form::CommentForm;
+use crate::model::{CommentError, CommentSort, CommentThread, PostedComment, ReviewSummary};
+
+/// Fixed community post ID — stable across page loads so comments persist.
+pub const COMMUNITY_POST_ID: &str = "community-main";
+
+/// Sentences quoted per sentiment class in the summary.
+const SUMMARY_SENTENCES: u32 = 3;
+
+/// Threads shown so far and the cursor of the next page.
+type Loaded = RwSignal<Option<(Vec<CommentThread>, Option<String>)>>;
+
+/// Top-level reviews fetched per page.
+const PAGE_SIZE: u32 = 20;
+
+/// Distance from the bottom of the page, in pixels, at which the next page
+/// is fetched.
+const LOAD_MORE_MARGIN: f64 = 400.0;
+
+/// Whether the page is scrolled to within `LOAD_MORE_MARGIN` of its end.
+fn near_bottom() -> bool {
+    let window = window();
+    let viewport = window.inner_height().ok().and_then(|h| h.as_f64()).unwrap_or(0.0);
+    let scrolled = window.scroll_y().unwrap_or(0.0);
+    let total = document().document_element().map(|e| e.scroll_height() as f64).unwrap_or(0.0);
+    scrolled + viewport >= total - LOAD_MORE_MARGIN
+}
+
+/// The `status` a filter tab lists.
+fn filter_status(filter: &str) -> Option<u8> {
+    match filter {
+        "negative" => Some(1),
+        "neutral" => Some(2),
+        "positive" => Some(3),
+        _ => None,
+    }
+}
+
+/// Fetch again the loaded thread holding the comment `id`, e.g. after a
+/// reply was added to or deleted from it.
+async fn refresh_thread(loaded: Loaded, id: String) -> Result<(), ServerFnError<CommentError>> {
+    let root = loaded.with_untracked(|loaded| {
+        let threads = loaded.as_ref().map(|(threads, _)| threads.as_slice()).unwrap_or_default();
+        threads.iter().find(|t| t.contains(&id)).and_then(|t| t.comment.id.clone())
+    });
+    let Some(root) = root else { return Ok(()) };
+    let thread = get_thread(root.clone()).await?;
+    loaded.update(|loaded| {
+        let mut threads = loaded.iter_mut().flat_map(|(threads, _)| threads.iter_mut());
+        if let Some(t) = threads.find(|t| t.comment.id.as_deref() == Some(root.as_str())) {
+            *t = thread;
+        }
+    });
+    Ok(())
+}
+
+/// Record a negative review (or reply) on the Soroban contract, signed by
+/// its author's wallet.
+async fn submit_if_negative(posted: PostedComment, author: String, set_error_msg: WriteSignal<Option<String>>) {
+    let PostedComment { comment, negative_sample } = posted;
+    if !negative_sample {
+        return;
+    }
+    web_sys::console::log_1(&"Negative comment detected...".into());
+    match get_soroban_config().await {
+         Ok(config) => {
+             // The stored content, with personal data masked, never the raw input
+             let content_hash = crate::model::onchain_excerpt(&comment.content);
+             match crate::components::wallet::submit_negative_comment_client(
+                 config.rpc_url, config.network_passphrase, config.contract_id,
+                 comment.id.clone().unwrap_or_default(), comment.scoring as u32, content_hash, author
+             ).await {
+                 Ok(res) => {
+                     if res.starts_with("SUCCESS:") {
+                         let hash = res.trim_start_matches("SUCCESS:");
+                         web_sys::console::log_1(&format!("✅ Soroban TX Success: {}", hash).into());
+                     } else {
+                         web_sys::console::error_1(&format!("❌ Soroban TX Failed: {}", res).into());
+                         set_error_msg.set(Some(format!("Submission Error: {}", res)));
+                     }
+                 },
+                 Err(e) => web_sys::console::error_1(&format!("TX Exception: {}", e).into())
+             }
+         }
+         Err(e) => web_sys::console::error_1(&format!("Config error: {}", e).into())
+    }
+}
+
+#[component]
+fn SummaryPanel(summary: ReviewSummary) -> impl IntoView {
+    let classes: Vec<_> = summary
+        .classes()
+        .into_iter()
+        .filter(|(_, class)| !class.highlights.is_empty())
+        .map(|(label, class)| (label, class.clone()))
+        .collect();
+    view! {
+        <div class="summary-panel">
+            <h2 class="summary-title">{format!("In short, from {} reviews", summary.comments)}</h2>
+            {classes.into_iter().map(|(label, class)| view! {
+                <div class=format!("summary-class summary-{}", label)>
+                    <span class="summary-label">{format!("{} · {} reviews", label, class.comments)}</span>
+                    <ul class="summary-sentences">
+                        {class.highlights.into_iter().map(|h| view! { <li>{format!("“{}”", h.text)}</li> }).collect_view()}
+                    </ul>
+                </div>
+            }).collect_view()}
+        </div>
+    }
+}
 
 #[component]
 pub fn Community(
     wallet_public_key: ReadSignal<String>,
 ) -> impl IntoView {
-    // Fixed community post ID — stable across page loads so comments persist
-    // Fixed community post ID — stable across page loads so comments persist
-    let (post_id, _set_post_id) = signal("community-main".to_string());
+    let (post_id, _set_post_id) = signal(COMMUNITY_POST_ID.to_string());
+
+    let (filter, set_filter) = signal("all".to_string());
+    let (sort, set_sort) = signal(CommentSort::default());
+
+    // First page of threads for the current filter and sort (SSR
+    // compatible); the filter applies to top-level reviews
+    let threads_res = Resource::new(
+        move || (post_id.get(), filter_status(&filter.get()), sort.get()),
+        |(pid, status, sort)| get_threads(pid, status, sort, None, PAGE_SIZE)
+    );
+
+    // Replaced whenever the first page is (re)fetched, extended on scroll
+    let loaded: Loaded = RwSignal::new(None);
+    let (loading_more, set_loading_more) = signal(false);
+    Effect::new(move |_| {
+        if let Some(Ok(page)) = threads_res.get() {
+            loaded.set(Some((page.threads, page.next_cursor)));
+        }
+    });
 
-    // Resource for fetching comments (SSR compatible)
-    let comments_res = Resource::new(
+    // Summary of all reviews; the server caches it until a comment changes
+    let summary_res = Resource::new(
         move || post_id.get(),
-        move |pid| get_comments_by_post(pid)
+        |pid| get_review_summary(pid, 0, SUMMARY_SENTENCES)
     );
 
     let (error_msg, set_error_msg) = signal(Option::<String>::None);
-    let (filter, set_filter) = signal("all".to_string());
+
+    let load_more = move || {
+        let Some((_, Some(cursor))) = loaded.get_untracked() else { return };
+        if loading_more.get_untracked() {
+            return;
+        }
+        set_loading_more.set(true);
+        let (pid, status, sort) = (post_id.get_untracked(), filter_status(&filter.get_untracked()), sort.get_untracked());
+        leptos::task::spawn_local(async move {
+            match get_threads(pid, status, sort, Some(cursor.clone()), PAGE_SIZE).await {
+                // Dropped if the list was reloaded meanwhile
+                Ok(page) => loaded.update(|loaded| {
+                    if let Some((threads, next)) = loaded.as_mut().filter(|(_, next)| next.as_ref() == Some(&cursor)) {
+                        threads.extend(page.threads);
+                        *next = page.next_cursor;
+                    }
+                }),
+                Err(e) => set_error_msg.set(Some(format!("Could not load more reviews: {}", error_message(&e)))),
+            }
+            set_loading_more.set(false);
+        });
+    };
+    let scroll = window_event_listener(leptos::ev::scroll, move |_| {
+        if near_bottom() {
+            load_more();
+        }
+    });
+    on_cleanup(move || scroll.remove());
 
     // Submit a new comment
     let on_submit = move |pid: String, author: String, content: String| {
         leptos::task::spawn_local(async move {
-            match create_comment(pid.clone(), author.clone(), content.clone(), None, 0).await {
-                Ok(comment) => {
+            match create_comment(pid, author.clone(), content, None).await {
+                Ok(posted) => {
                     // Refetch
-                    comments_res.refetch();
-                    
-                    if comment.status == 1 {
-                        web_sys::console::log_1(&"Negative comment detected...".into());
-                        match get_soroban_config().await {
-                             Ok(config) => {
-                                 let content_hash = if content.len() > 32 { content[..32].to_string() + "..." } else { content.clone() };
-                                 match crate::components::wallet::submit_negative_comment_client(
-                                     config.rpc_url, config.network_passphrase, config.contract_id,
-                                     comment.id.clone().unwrap_or_default(), comment.scoring as u32, content_hash, author
-                                 ).await {
-                                     Ok(res) => {
-                                         if res.starts_with("SUCCESS:") {
-                                             let hash = res.trim_start_matches("SUCCESS:");
-                                             web_sys::console::log_1(&format!("✅ Soroban TX Success: {}", hash).into());
-                                         } else {
-                                             web_sys::console::error_1(&format!("❌ Soroban TX Failed: {}", res).into());
-                                             set_error_msg.set(Some(format!("Submission Error: {}", res)));
-                                         }
-                                     },
-                                     Err(e) => web_sys::console::error_1(&format!("TX Exception: {}", e).into())
-                                 }
-                             }
-                             Err(e) => web_sys::console::error_1(&format!("Config error: {}", e).into())
-                        }
-                    }
+                    threads_res.refetch();
+                    summary_res.refetch();
+                    submit_if_negative(posted, author, set_error_msg).await;
                 }
-                Err(e) => set_error_msg.set(Some(format!("Failed to post: {}", e)))
+                Err(e) => set_error_msg.set(Some(format!("Failed to post: {}", error_message(&e))))
             }
         });
     };
 
+    // Reply callback: the server places the reply under its parent
+    let on_reply = Callback::new(move |(parent, content): (String, String)| {
+        let (pid, author) = (post_id.get_untracked(), wallet_public_key.get_untracked());
+        leptos::task::spawn_local(async move {
+            match create_comment(pid, author.clone(), content, Some(parent.clone())).await {
+                Ok(posted) => {
+                    if let Err(e) = refresh_thread(loaded, parent).await {
+                        set_error_msg.set(Some(format!("Could not load replies: {}", error_message(&e))));
+                    }
+                    summary_res.refetch();
+                    submit_if_negative(posted, author, set_error_msg).await;
+                }
+                Err(e) => set_error_msg.set(Some(format!("Failed to reply: {}", error_message(&e))))
+            }
+        });
+    });
+
     // Like callback
     let on_like = Callback::new(move |id: String| {
         leptos::task::spawn_local(async move {
             match like_comment(id.clone()).await {
-                Ok(_) => comments_res.refetch(),
-                Err(e) => web_sys::console::log_1(&format!("Error liking: {}", e).into())
+                Ok(liked) => loaded.update(|loaded| {
+                    let mut threads = loaded.iter_mut().flat_map(|(threads, _)| threads.iter_mut());
+                    if let Some(comment) = threads.find_map(|t| t.comment_mut(&id)) {
+                        *comment = liked;
+                    }
+                }),
+                Err(e) => set_error_msg.set(Some(format!("Could not like: {}", error_message(&e))))
             }
         });
     });
 
-    // Delete callback
+    // Delete callback: a top-level review leaves with its replies, a
+    // deleted reply is dropped from its thread
     let on_delete = Callback::new(move |id: String| {
         leptos::task::spawn_local(async move {
-            match delete_comment(id).await {
-                Ok(_) => comments_res.refetch(),
-                Err(e) => web_sys::console::log_1(&format!("Error deleting: {}", e).into())
+            match delete_comment(id.clone()).await {
+                Ok(_) => {
+                    let top_level = loaded.with_untracked(|loaded| {
+                        loaded.iter().flat_map(|(threads, _)| threads).any(|t| t.comment.id.as_deref() == Some(id.as_str()))
+                    });
+                    if top_level {
+                        loaded.update(|loaded| {
+                            if let Some((threads, _)) = loaded {
+                                threads.retain(|t| t.comment.id.as_deref() != Some(id.as_str()));
+                            }
+                        });
+                    } else if let Err(e) = refresh_thread(loaded, id).await {
+                        set_error_msg.set(Some(format!("Could not load replies: {}", error_message(&e))));
+                    }
+                    summary_res.refetch();
+                }
+                Err(e) => set_error_msg.set(Some(format!("Could not delete: {}", error_message(&e))))
             }
         });
     });
 
-    // Derived signals from resource
-    let comments_data = move || comments_res.get()
+    // Every comment in reading order with its reply count. Before the first
+    // page is copied into `loaded` (SSR, hydration) it is shown straight
+    // from the resource
+    let comments_data = move || loaded.get()
+        .map(|(threads, _)| threads)
+        .or_else(|| threads_res.get().and_then(|r| r.ok()).map(|page| page.threads))
+        .unwrap_or_default()
+        .iter()
+        .flat_map(CommentThread::flatten)
+        .collect::<Vec<_>>();
+    let has_more = move || loaded.get().is_some_and(|(_, next)| next.is_some());
+
+    // Counts cover every review, not just the pages loaded so far
+    let counts = move || summary_res.get()
         .and_then(|r| r.ok())
+        .map(|s| (s.comments, s.negative.comments, s.neutral.comments, s.positive.comments))
         .unwrap_or_default();
-    
-    let filtered_comments = move || {
-        let current_filter = filter.get();
-        let all = comments_data();
-        match current_filter.as_str() {
-            "negative" => all.iter().filter(|c| c.status == 1).cloned().collect(),
-            "neutral" => all.iter().filter(|c| c.status == 2).cloned().collect(),
-            "positive" => all.iter().filter(|c| c.status == 3).cloned().collect(),
-            _ => all,
-        }
-    };
-
-    let comment_count = move || comments_data().len();
-    let neg_count = move || comments_data().iter().filter(|c| c.status == 1).count();
-    let neu_count = move || comments_data().iter().filter(|c| c.status == 2).count();
-    let pos_count = move || comments_data().iter().filter(|c| c.status == 3).count();
+    let comment_count = move || counts().0;
+    let neg_count = move || counts().1;
+    let neu_count = move || counts().2;
+    let pos_count = move || counts().3;
 
     view! {
         <div class="community-page">
@@ -108,6 +275,13 @@ pub fn Community(
                     <p class="community-subtitle">"Real experiences from our valued guests"</p>
                 </div>
 
+                <Suspense fallback=|| ()>
+                    {move || summary_res.get()
+                        .and_then(|r| r.ok())
+                        .filter(|s| s.comments > 0)
+                        .map(|summary| view! { <SummaryPanel summary=summary /> })}
+                </Suspense>
+
                 <Suspense fallback=move || view! { <div class="loading-spinner"></div> }>
                     <div class="stats-bar">
                         <div class="stat-item">
@@ -133,6 +307,11 @@ pub fn Community(
                          <button class="filter-tab filter-tab-negative" class:active=move || filter.get() == "negative" on:click=move |_| set_filter.set("negative".to_string())>"Negative"</button>
                          <button class="filter-tab filter-tab-neutral" class:active=move || filter.get() == "neutral" on:click=move |_| set_filter.set("neutral".to_string())>"Neutral"</button>
                          <button class="filter-tab filter-tab-positive" class:active=move || filter.get() == "positive" on:click=move |_| set_filter.set("positive".to_string())>"Positive"</button>
+                         <select class="sort-select" on:change=move |ev| set_sort.set(CommentSort::parse(&event_target_value(&ev)).unwrap_or_default())>
+                             {CommentSort::ALL.into_iter().map(|s| view! {
+                                 <option value=s.as_str() selected=move || sort.get() == s>{s.label()}</option>
+                             }).collect_view()}
+                         </select>
                     </div>
 
                     <CommentForm
@@ -147,20 +326,27 @@ pub fn Community(
 
                     <div class="comments-list">
                         <For
-                            each=filtered_comments
-                            key=|comment| comment.id.clone().unwrap_or_default()
-                            let:comment
+                            each=comments_data
+                            key=|(comment, replies)| (comment.id.clone().unwrap_or_default(), comment.likes_count, *replies)
+                            let:entry
                         >
                             <CommentCard
-                                comment=comment
+                                comment=entry.0
+                                reply_count=entry.1
                                 on_like=on_like
                                 on_delete=on_delete
+                                on_reply=on_reply
                                 current_user=wallet_public_key
                             />
                         </For>
-                        <Show when=move || filtered_comments().is_empty()>
+                        <Show when=move || comments_data().is_empty()>
                              <div class="empty-state"><p>"No reviews yet."</p></div>
                         </Show>
+                        <Show when=has_more>
+                            <button class="load-more" disabled=loading_more on:click=move |_| load_more()>
+                                {move || if loading_more.get() { "Loading…" } else { "Load more" }}
+                            </button>
+                        </Show>
                     </div>
                 </Suspense>
             </div>
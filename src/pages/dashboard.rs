@@ -1,7 +1,41 @@
 use leptos::prelude::*;
+use crate::api::{get_aspect_summary, get_top_keyphrases};
+use crate::model::AspectSummary;
+use crate::pages::community::COMMUNITY_POST_ID;
+
+/// Width of one bar segment as a CSS percentage of all mentions.
+fn share(count: u32, total: u32) -> String {
+    format!("width: {}%", if total == 0 { 0.0 } else { count as f64 * 100.0 / total as f64 })
+}
+
+#[component]
+fn AspectRow(summary: AspectSummary) -> impl IntoView {
+    let total = summary.mentions();
+    view! {
+        <div class="aspect-row">
+            <span class="aspect-name">{summary.aspect.title()}</span>
+            <div class="aspect-bar">
+                <div class="aspect-bar-negative" style=share(summary.negative, total)></div>
+                <div class="aspect-bar-neutral" style=share(summary.neutral, total)></div>
+                <div class="aspect-bar-positive" style=share(summary.positive, total)></div>
+            </div>
+            <span class="aspect-count">{total}</span>
+        </div>
+    }
+}
+
+/// Window and length of the trending complaints list.
+const TRENDING_DAYS: u32 = 7;
+const TRENDING_PHRASES: u32 = 8;
 
 #[component]
 pub fn Dashboard() -> impl IntoView {
+    let aspects_res = Resource::new(|| COMMUNITY_POST_ID.to_string(), get_aspect_summary);
+    let trending_res = Resource::new(
+        || COMMUNITY_POST_ID.to_string(),
+        |post_id| get_top_keyphrases(post_id, TRENDING_DAYS, TRENDING_PHRASES),
+    );
+
     view! {
         <div class="dashboard-page">
             // Hero Section
@@ -73,6 +107,48 @@ pub fn Dashboard() -> impl IntoView {
                 </div>
             </section>
 
+            // Guest Feedback by Aspect
+            <section class="aspects-section">
+                <div class="section-container">
+                    <div class="section-header">
+                        <span class="section-badge">"Guest Feedback"</span>
+                        <h2 class="section-title">"What Our Guests Say"</h2>
+                    </div>
+                    <Suspense fallback=move || view! { <div class="loading-spinner"></div> }>
+                        {move || {
+                            let summaries = aspects_res.get().and_then(|r| r.ok()).unwrap_or_default();
+                            if summaries.is_empty() {
+                                view! { <div class="empty-state"><p>"No reviews yet."</p></div> }.into_any()
+                            } else {
+                                view! {
+                                    <div class="aspect-chart">
+                                        {summaries.into_iter().map(|summary| view! { <AspectRow summary=summary /> }).collect_view()}
+                                    </div>
+                                }.into_any()
+                            }
+                        }}
+                    </Suspense>
+                    <Suspense fallback=|| ()>
+                        {move || {
+                            let complaints = trending_res.get().and_then(|r| r.ok()).map(|t| t.negative).unwrap_or_default();
+                            (!complaints.is_empty()).then(|| view! {
+                                <div class="trending">
+                                    <h3 class="trending-title">"Trending complaints this week"</h3>
+                                    <ul class="trending-list">
+                                        {complaints.into_iter().map(|c| view! {
+                                            <li class="trending-item">
+                                                <span class="trending-phrase">{c.phrase}</span>
+                                                <span class="trending-count">{format!("×{}", c.count)}</span>
+                                            </li>
+                                        }).collect_view()}
+                                    </ul>
+                                </div>
+                            })
+                        }}
+                    </Suspense>
+                </div>
+            </section>
+
             // Features Section
             <section class="features-section">
                 <div class="section-container">
@@ -0,0 +1,31 @@
+use crate::model::CommentError;
+
+/// Check an admin token against `ADMIN_TOKEN`. Admin actions are disabled
+/// entirely while `ADMIN_TOKEN` is unset or empty.
+pub fn require_admin(token: &str) -> Result<(), CommentError> {
+    let expected = std::env::var("ADMIN_TOKEN").unwrap_or_default();
+    if expected.trim().is_empty() {
+        return Err(CommentError::Forbidden("Admin actions are disabled (ADMIN_TOKEN is not set)".to_string()));
+    }
+    if !constant_time_eq(token.trim().as_bytes(), expected.trim().as_bytes()) {
+        return Err(CommentError::Forbidden("Invalid admin token".to_string()));
+    }
+    Ok(())
+}
+
+/// Compare without returning early on the first differing byte.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+    }
+}
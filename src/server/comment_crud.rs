@@ -1,246 +1,456 @@
-use bson::{doc, oid::ObjectId, Document};
-use futures::TryStreamExt;
-use crate::model::Comment;
-use super::db::get_comments_collection;
-use super::sentiment::{analyze_sentiment, compute_scoring};
-
-/// Convert a BSON Document to our Comment model.
-fn doc_to_comment(doc: &Document) -> Option<Comment> {
-    Some(Comment {
-        id: doc.get_object_id("_id").ok().map(|id| id.to_hex()),
-        post_id: doc.get_str("post_id").ok()?.to_string(),
-        author_public_key: doc.get_str("author_public_key").ok()?.to_string(),
-        content: doc.get_str("content").ok()?.to_string(),
-        parent_id: doc.get_object_id("parent_id").ok().map(|id| id.to_hex()),
-        depth: doc.get_i32("depth").ok().unwrap_or(0) as u8,
-        status: doc.get_i32("status").ok().unwrap_or(2) as u8,
-        scoring: doc.get_i32("scoring").ok().unwrap_or(0) as u8,
-        likes_count: doc.get_i32("likes_count").ok().unwrap_or(0),
-        is_deleted: doc.get_bool("is_deleted").ok().unwrap_or(false),
-        created_at: doc.get_datetime("created_at")
-            .ok()
-            .map(|dt| dt.timestamp_millis())
-            .unwrap_or(0),
-        updated_at: doc.get_datetime("updated_at")
-            .ok()
-            .map(|dt| dt.timestamp_millis())
-            .unwrap_or(0),
-    })
-}
-
-/// Create a new comment.
-pub async fn create_comment(
-    post_id: &str,
-    author_public_key: &str,
-    content: &str,
-    parent_id: Option<String>,
-    depth: u8,
-) -> Result<Comment, String> {
-    let col = get_comments_collection();
-    let now = bson::DateTime::now();
-
-    let sentiment_status = analyze_sentiment(content);
-    let scoring = compute_scoring(content);
-
-    // Store post_id as a string (supports both ObjectId and human-readable IDs)
+//! Comment operations: the rules applied on every write — spam checks,
+//! personal data masking, sentiment analysis, toxicity — on top of the
+//! configured [`CommentStore`](super::store::CommentStore).
+
+use crate::model::{
+    AspectSummary, Comment, CommentError, CommentPage, CommentSort, CommentThread, LabelOverride, ModerationStatus,
+    ThreadPage, TopKeyphrases, MAX_DEPTH,
+};
+use super::sentiment::{self, Analysis};
+use super::redaction;
+use super::spam;
+use super::store::{not_found, parse_id, store, AnalysisFields, ContentEdit, Listing, PageCursor, ToxicityFields};
+use super::summary;
+
+/// Mask personal data in `content`. Returns the text to store and analyze,
+/// whether anything was masked and, when originals are encrypted and
+/// something was masked, the sealed original.
+fn content_fields(content: &str) -> Result<(String, bool, Option<String>), CommentError> {
+    let redaction = redaction::redact(content);
+    let mut sealed = None;
+    if redaction.is_redacted() {
+        log::info!("Masked {} personal data item(s) in a comment", redaction.found.len());
+        sealed = redaction::policy().seal(content).map_err(CommentError::Storage)?;
+    }
+    let redacted = redaction.is_redacted();
+    Ok((redaction.text, redacted, sealed))
+}
 
-    let parent_oid = match &parent_id {
-        Some(pid) => Some(ObjectId::parse_str(pid).map_err(|e| format!("Invalid parent_id: {}", e))?),
-        None => None,
-    };
+/// Depth of a reply to `parent_id` on `post_id`: one below a live parent on
+/// the same post, at most `MAX_DEPTH`. 0 for a top-level comment.
+async fn reply_depth(post_id: &str, parent_id: Option<&str>) -> Result<u8, CommentError> {
+    let Some(parent_id) = parent_id else { return Ok(0) };
+    parse_id(parent_id).map_err(|e| CommentError::InvalidId(format!("Invalid parent_id: {}", e.message())))?;
+    let parent = get_visible_comment(parent_id)
+        .await?
+        .ok_or_else(|| CommentError::NotFound("Parent comment not found".to_string()))?;
+    if parent.post_id != post_id {
+        return Err(CommentError::Validation("A reply must be on the same post as its parent".to_string()));
+    }
+    if parent.depth >= MAX_DEPTH {
+        return Err(CommentError::Validation(format!("Replies can be nested at most {} levels deep", MAX_DEPTH)));
+    }
+    Ok(parent.depth + 1)
+}
 
-    let mut doc = doc! {
-        "post_id": post_id,
-        "author_public_key": author_public_key,
-        "content": content,
-        "depth": depth as i32,
-        "status": sentiment_status as i32,
-        "scoring": scoring as i32,
-        "likes_count": 0_i32,
-        "is_deleted": false,
-        "created_at": now,
-        "updated_at": now,
+/// Spam-check `content` by `author` on `post_id` against the post's recent
+/// comments, other than the comment being edited if any. Returns the spam
+/// score, or why the text may not be posted. Edits are not posts, so they
+/// do not count towards a burst.
+async fn check_spam(post_id: &str, author: &str, content: &str, editing: Option<&str>) -> Result<u8, CommentError> {
+    let since = |secs: i64| bson::DateTime::now().timestamp_millis() - secs * 1000;
+    let mut recent = store().list_recent(post_id, since(spam::RECENT_WINDOW_SECS), spam::RECENT_LIMIT as usize).await?;
+    recent.retain(|c| editing.is_none_or(|id| c.id.as_deref() != Some(id)));
+    let burst = match editing {
+        Some(_) => 0,
+        None => store().count_by_author_since(author, since(spam::BURST_WINDOW_SECS)).await?,
     };
-
-    if let Some(pid) = parent_oid {
-        doc.insert("parent_id", pid);
+    let spam_check = spam::check(content, author, &recent, burst);
+    if let Some(reason) = spam_check.rejection {
+        log::warn!("Rejected comment by {} on {} (spam score {}): {}", author, post_id, spam_check.score, reason.message());
+        return Err(reason);
     }
+    Ok(spam_check.score)
+}
 
-    let result = col.insert_one(doc).await.map_err(|e| format!("Insert error: {}", e))?;
-
-    let inserted_id = result
-        .inserted_id
-        .as_object_id()
-        .ok_or("No inserted ID returned")?;
+/// Create a new comment, or a reply to `parent_id`, unless it is spam.
+/// Personal data is masked before anything is analyzed or stored.
+pub async fn create_comment(
+    post_id: &str,
+    author_public_key: &str,
+    content: &str,
+    parent_id: Option<String>,
+) -> Result<Comment, CommentError> {
+    let depth = reply_depth(post_id, parent_id.as_deref()).await?;
+    let now = bson::DateTime::now().timestamp_millis();
 
-    let id_str = inserted_id.to_hex();
+    // Spam checks see the raw text: phone numbers and links are a signal
+    let spam_score = check_spam(post_id, author_public_key, content, None).await?;
+    let (content, redacted, content_original) = content_fields(content)?;
+    let analysis = sentiment::analyze(&content);
 
-    Ok(Comment {
-        id: Some(id_str),
+    let mut comment = Comment {
+        id: None,
         post_id: post_id.to_string(),
         author_public_key: author_public_key.to_string(),
-        content: content.to_string(),
+        content,
+        redacted,
         parent_id,
         depth,
-        status: sentiment_status,
-        scoring,
+        status: 2,
+        scoring: 50,
+        auto_status: 2,
+        human_status: None,
+        confidence: 1.0,
+        needs_review: false,
+        label_overrides: Vec::new(),
+        language: None,
+        unanalyzed: false,
+        analyzer_version: None,
+        aspects: Vec::new(),
+        evidence: Vec::new(),
+        sentences: Vec::new(),
+        keyphrases: Vec::new(),
+        toxicity: 0,
+        toxic_terms: Vec::new(),
+        moderation: ModerationStatus::Visible,
+        spam_score,
         likes_count: 0,
         is_deleted: false,
-        created_at: now.timestamp_millis(),
-        updated_at: now.timestamp_millis(),
-    })
+        created_at: now,
+        updated_at: now,
+    };
+    AnalysisFields::new(&analysis, None).apply(&mut comment);
+    ToxicityFields::new(&comment.content).apply(&mut comment);
+
+    let comment = store().create(comment, content_original).await?;
+    summary::invalidate(post_id);
+    log::info!(
+        "Comment {} by {} spam score {}",
+        comment.id.as_deref().unwrap_or_default(),
+        author_public_key,
+        spam_score
+    );
+    Ok(comment)
 }
 
-/// Get all non-deleted comments for a post, sorted by creation time.
-pub async fn get_comments_by_post(post_id: &str) -> Result<Vec<Comment>, String> {
-    let col = get_comments_collection();
-    // Use string-based post_id matching
-
-    let filter = doc! {
-        "post_id": post_id,
-        "is_deleted": false,
-    };
+/// Comments per page when the caller asks for 0.
+pub const DEFAULT_PAGE_SIZE: u32 = 20;
+/// Most comments per page.
+pub const MAX_PAGE_SIZE: u32 = 100;
+
+/// One page of `listing` in `sort` order: up to `limit` comments (0 = the
+/// default, at most `MAX_PAGE_SIZE`) after `cursor`, the `next_cursor` of
+/// the previous page. Pages are keyed by the sort value and id, so comments
+/// added meanwhile do not shift them; a comment whose like count or score
+/// changes between requests may be skipped or repeated.
+pub async fn get_comment_page(
+    listing: &Listing,
+    sort: CommentSort,
+    cursor: Option<&str>,
+    limit: u32,
+) -> Result<CommentPage, CommentError> {
+    let limit = if limit == 0 { DEFAULT_PAGE_SIZE } else { limit.min(MAX_PAGE_SIZE) } as usize;
+    let after = cursor.filter(|c| !c.trim().is_empty()).map(|c| PageCursor::decode(c, sort)).transpose()?;
+
+    // One extra tells whether there is a next page
+    let mut comments = store().list_page(listing, sort, after.as_ref(), limit + 1).await?;
+    let mut next_cursor = None;
+    if comments.len() > limit {
+        comments.truncate(limit);
+        next_cursor = comments.last().map(|c| PageCursor::after(sort, c).encode(sort));
+    }
+    Ok(CommentPage { comments, next_cursor })
+}
 
-    let mut cursor = col
-        .find(filter)
-        .sort(doc! { "created_at": 1 })
-        .await
-        .map_err(|e| format!("Find error: {}", e))?;
+/// Start of a window of the last `days` days in milliseconds since epoch;
+/// `None` for 0 (all time).
+fn window_start(days: u32) -> Option<i64> {
+    (days > 0).then(|| bson::DateTime::now().timestamp_millis() - days as i64 * 24 * 3600 * 1000)
+}
 
-    let mut comments = Vec::new();
-    while let Some(doc) = cursor.try_next().await.map_err(|e| format!("Cursor error: {}", e))? {
-        if let Some(comment) = doc_to_comment(&doc) {
-            comments.push(comment);
+/// `roots` with all their replies, in the order given: one store query
+/// per level of replies.
+async fn threads_of(roots: Vec<Comment>) -> Result<Vec<CommentThread>, CommentError> {
+    let mut replies = Vec::new();
+    let mut parents: Vec<String> = roots.iter().filter_map(|c| c.id.clone()).collect();
+    for _ in 0..MAX_DEPTH {
+        if parents.is_empty() {
+            break;
         }
+        let level = store().list_replies(&parents).await?;
+        parents = level.iter().filter_map(|c| c.id.clone()).collect();
+        replies.extend(level);
     }
-
-    Ok(comments)
+    Ok(CommentThread::build(roots, replies))
 }
 
-/// Get a single comment by ID.
-pub async fn get_comment(id: &str) -> Result<Option<Comment>, String> {
-    let col = get_comments_collection();
-    let oid = ObjectId::parse_str(id).map_err(|e| format!("Invalid id: {}", e))?;
-
-    let doc = col
-        .find_one(doc! { "_id": oid })
-        .await
-        .map_err(|e| format!("Find error: {}", e))?;
+/// One page of a post's top-level comments (only those with `status` if
+/// given), each with all its replies. Paged like `get_comment_page`.
+pub async fn get_thread_page(
+    post_id: &str,
+    status: Option<u8>,
+    sort: CommentSort,
+    cursor: Option<&str>,
+    limit: u32,
+) -> Result<ThreadPage, CommentError> {
+    let listing = Listing::Post { post_id: post_id.to_string(), status, top_level: true };
+    let page = get_comment_page(&listing, sort, cursor, limit).await?;
+    Ok(ThreadPage { threads: threads_of(page.comments).await?, next_cursor: page.next_cursor })
+}
 
-    Ok(doc.as_ref().and_then(|d| doc_to_comment(d)))
+/// A live comment with all its replies.
+pub async fn get_thread(id: &str) -> Result<CommentThread, CommentError> {
+    let comment = get_visible_comment(id).await?.ok_or_else(not_found)?;
+    let mut threads = threads_of(vec![comment]).await?;
+    threads.pop().ok_or_else(not_found)
 }
 
-/// Update a comment's content (re-runs sentiment analysis).
-pub async fn update_comment(id: &str, new_content: &str) -> Result<Comment, String> {
-    let col = get_comments_collection();
-    let oid = ObjectId::parse_str(id).map_err(|e| format!("Invalid id: {}", e))?;
-    let now = bson::DateTime::now();
+/// Get the non-deleted, non-hidden comments on a post from the last `days`
+/// days (0 = all time), sorted by creation time.
+pub async fn get_comments_by_post_since(post_id: &str, days: u32) -> Result<Vec<Comment>, CommentError> {
+    store().list_by_post(post_id, window_start(days)).await
+}
 
-    let sentiment_status = analyze_sentiment(new_content);
-    let scoring = compute_scoring(new_content);
+/// Get a single comment by ID, deleted and hidden ones included (admin).
+pub async fn get_comment(id: &str) -> Result<Option<Comment>, CommentError> {
+    store().get(id).await
+}
 
-    col.update_one(
-        doc! { "_id": oid },
-        doc! {
-            "$set": {
-                "content": new_content,
-                "status": sentiment_status as i32,
-                "scoring": scoring as i32,
-                "updated_at": now,
-            }
-        },
-    )
-    .await
-    .map_err(|e| format!("Update error: {}", e))?;
+/// Get a single comment by ID unless it was deleted or hidden: what anyone
+/// may read.
+pub async fn get_visible_comment(id: &str) -> Result<Option<Comment>, CommentError> {
+    Ok(get_comment(id)
+        .await?
+        .filter(|c| !c.is_deleted && c.moderation != ModerationStatus::Hidden))
+}
 
+/// Get a comment that was just written.
+async fn reload(id: &str, after: &str) -> Result<Comment, CommentError> {
     get_comment(id)
         .await?
-        .ok_or_else(|| "Comment not found after update".to_string())
+        .ok_or_else(|| CommentError::NotFound(format!("Comment not found after {}", after)))
 }
 
-/// Like a comment (increment likes_count).
-pub async fn like_comment(id: &str) -> Result<Comment, String> {
-    let col = get_comments_collection();
-    let oid = ObjectId::parse_str(id).map_err(|e| format!("Invalid id: {}", e))?;
-
-    col.update_one(
-        doc! { "_id": oid },
-        doc! {
-            "$inc": { "likes_count": 1 },
-            "$set": { "updated_at": bson::DateTime::now() },
-        },
-    )
-    .await
-    .map_err(|e| format!("Like error: {}", e))?;
+/// Update a comment's content unless the new text is spam (masks personal
+/// data, re-runs sentiment analysis and toxicity classification, which may
+/// queue or hide it but never makes a queued or hidden comment visible). A
+/// human label was given to the old text and is dropped.
+pub async fn update_comment(id: &str, new_content: &str) -> Result<Comment, CommentError> {
+    let current = get_comment(id).await?.ok_or_else(not_found)?;
+    let spam_score = check_spam(&current.post_id, &current.author_public_key, new_content, Some(id)).await?;
+    let (content, redacted, content_original) = content_fields(new_content)?;
+    let analysis = sentiment::analyze(&content);
+
+    let edit = ContentEdit {
+        analysis: AnalysisFields::new(&analysis, None),
+        toxicity: ToxicityFields::new(&content),
+        spam_score,
+        content,
+        redacted,
+        content_original,
+        updated_at: bson::DateTime::now().timestamp_millis(),
+    };
+    store().update(id, &edit).await?;
 
-    get_comment(id)
-        .await?
-        .ok_or_else(|| "Comment not found after like".to_string())
+    let comment = reload(id, "update").await?;
+    summary::invalidate(&comment.post_id);
+    Ok(comment)
+}
+
+/// Replace a comment's stored analysis without touching its content,
+/// `updated_at` or human label (used by re-analysis).
+pub async fn set_analysis(
+    id: &str,
+    post_id: &str,
+    analysis: &Analysis,
+    human_status: Option<u8>,
+) -> Result<(), CommentError> {
+    store().set_analysis(id, &AnalysisFields::new(analysis, human_status)).await?;
+    summary::invalidate(post_id);
+
+    Ok(())
+}
+
+/// Like a comment (increment likes_count).
+pub async fn like_comment(id: &str) -> Result<Comment, CommentError> {
+    store().like(id).await?;
+    reload(id, "like").await
 }
 
 /// Soft-delete a comment.
-pub async fn soft_delete_comment(id: &str) -> Result<(), String> {
-    let col = get_comments_collection();
-    let oid = ObjectId::parse_str(id).map_err(|e| format!("Invalid id: {}", e))?;
-
-    col.update_one(
-        doc! { "_id": oid },
-        doc! {
-            "$set": {
-                "is_deleted": true,
-                "updated_at": bson::DateTime::now(),
-            }
-        },
-    )
-    .await
-    .map_err(|e| format!("Delete error: {}", e))?;
+pub async fn soft_delete_comment(id: &str) -> Result<(), CommentError> {
+    store().soft_delete(id).await?;
+    summary::clear();
 
     Ok(())
 }
 
-/// Get all comments (for admin/debug purposes), including deleted.
-pub async fn get_all_comments() -> Result<Vec<Comment>, String> {
-    let col = get_comments_collection();
+/// Get live comments that are queued for moderation or were hidden,
+/// most toxic first.
+pub async fn get_moderation_queue() -> Result<Vec<Comment>, CommentError> {
+    store().moderation_queue().await
+}
 
-    let mut cursor = col
-        .find(doc! {})
-        .sort(doc! { "created_at": -1 })
-        .await
-        .map_err(|e| format!("Find error: {}", e))?;
+/// Record a moderator's decision on a comment.
+pub async fn set_moderation(id: &str, moderation: ModerationStatus) -> Result<Comment, CommentError> {
+    store().set_moderation(id, moderation).await?;
 
-    let mut comments = Vec::new();
-    while let Some(doc) = cursor.try_next().await.map_err(|e| format!("Cursor error: {}", e))? {
-        if let Some(comment) = doc_to_comment(&doc) {
-            comments.push(comment);
-        }
+    let comment = reload(id, "moderation").await?;
+    summary::invalidate(&comment.post_id);
+    Ok(comment)
+}
+
+/// Get live comments whose label the analyzer was unsure of and no
+/// moderator has set, least confident first.
+pub async fn get_review_queue() -> Result<Vec<Comment>, CommentError> {
+    store().review_queue().await
+}
+
+/// Record a moderator's label (1=negative, 2=neutral, 3=positive). It
+/// becomes the comment's `status` and survives re-analysis; the change is
+/// appended to the comment's `label_overrides`.
+pub async fn set_human_label(id: &str, status: u8, moderator: &str) -> Result<Comment, CommentError> {
+    if !(1..=3).contains(&status) {
+        return Err(CommentError::Validation(format!("Invalid status: {}", status)));
+    }
+    let moderator = moderator.trim();
+    if moderator.is_empty() {
+        return Err(CommentError::Validation("Moderator name is required".to_string()));
     }
+    let old_status = get_comment(id)
+        .await?
+        .ok_or_else(not_found)?
+        .status;
+
+    let change = LabelOverride {
+        moderator: moderator.to_string(),
+        at: bson::DateTime::now().timestamp_millis(),
+        old_status,
+        new_status: status,
+    };
+    store().set_human_label(id, &change).await?;
 
-    Ok(comments)
+    let comment = reload(id, "labelling").await?;
+    summary::invalidate(&comment.post_id);
+    Ok(comment)
 }
 
-/// Get negative comments for a post.
-pub async fn get_negative_comments(post_id: &str) -> Result<Vec<Comment>, String> {
-    let col = get_comments_collection();
-    // Use string-based post_id matching
+/// Get live comments with a moderator's label, oldest first: the
+/// corrections the analyzer is evaluated and retrained against.
+pub async fn get_human_labelled_comments() -> Result<Vec<Comment>, CommentError> {
+    store().list_human_labelled().await
+}
 
-    let filter = doc! {
-        "post_id": post_id,
-        "status": 1_i32,
-        "is_deleted": false,
-    };
+/// Get up to `limit` comments (including deleted) with an id after
+/// `after`, in id order. Used to walk the whole collection in batches.
+pub async fn get_comments_after(after: Option<&str>, limit: i64) -> Result<Vec<Comment>, CommentError> {
+    store().list_after(after, limit.max(0) as usize).await
+}
 
-    let mut cursor = col
-        .find(filter)
-        .sort(doc! { "created_at": -1 })
-        .await
-        .map_err(|e| format!("Find error: {}", e))?;
+/// Get all negative comments for a post, newest first.
+pub async fn get_negative_comments(post_id: &str) -> Result<Vec<Comment>, CommentError> {
+    store().list_negative(post_id).await
+}
+
+/// Count non-hidden negative/neutral/positive comments per aspect for a
+/// post. Aspects nobody mentioned are left out.
+pub async fn get_aspect_summary(post_id: &str) -> Result<Vec<AspectSummary>, CommentError> {
+    store().aspect_summary(post_id).await
+}
 
-    let mut comments = Vec::new();
-    while let Some(doc) = cursor.try_next().await.map_err(|e| format!("Cursor error: {}", e))? {
-        if let Some(comment) = doc_to_comment(&doc) {
-            comments.push(comment);
+/// Most phrases returned per sentiment class.
+const MAX_KEYPHRASES: u32 = 50;
+
+/// Most frequent keyphrases of a post's visible comments per sentiment
+/// class, `limit` (at most `MAX_KEYPHRASES`) per class. Each comment counts a phrase at most once per
+/// class. With `days` > 0 only comments from the last `days` days count.
+pub async fn get_top_keyphrases(post_id: &str, days: u32, limit: u32) -> Result<TopKeyphrases, CommentError> {
+    let limit = limit.clamp(1, MAX_KEYPHRASES) as usize;
+    let top = store().top_keyphrases(post_id, window_start(days), limit).await?;
+    Ok(TopKeyphrases { days, ..top })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Runs against the in-memory store `store()` falls back to.
+    #[tokio::test]
+    async fn test_comment_lifecycle_without_a_database() {
+        let post = bson::oid::ObjectId::new().to_hex();
+        let author = format!("G{}", post);
+
+        let comment = create_comment(&post, &author, "The soup was cold and the waiter was rude", None)
+            .await
+            .unwrap();
+        let id = comment.id.clone().unwrap();
+        assert_eq!(comment.status, 1);
+        assert_eq!(get_negative_comments(&post).await.unwrap().len(), 1);
+
+        assert_eq!(like_comment(&id).await.unwrap().likes_count, 1);
+        let edited = update_comment(&id, "Lovely soup, friendly waiter, write to me at ana@example.com")
+            .await
+            .unwrap();
+        assert_eq!(edited.status, 3);
+        assert!(edited.redacted && !edited.content.contains("ana@example.com"));
+        assert_eq!(get_comments_by_post_since(&post, 0).await.unwrap().len(), 1);
+        let ad = update_comment(&id, "Cheap deals at www.example.com, call 0555 123 45 67").await;
+        assert!(matches!(ad, Err(CommentError::Validation(_))));
+
+        soft_delete_comment(&id).await.unwrap();
+        assert!(get_comments_by_post_since(&post, 0).await.unwrap().is_empty());
+        assert!(get_comment(&id).await.unwrap().unwrap().is_deleted);
+        assert!(get_visible_comment(&id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_comment_pages() {
+        let post = bson::oid::ObjectId::new().to_hex();
+        let reviews = ["Terrible soup, cold and bland", "Decent bread and fair prices", "Wonderful dessert, lovely staff"];
+        for (i, review) in reviews.into_iter().enumerate() {
+            create_comment(&post, &format!("G{}{}", i, post), review, None).await.unwrap();
         }
+        let listing = Listing::Post { post_id: post.clone(), status: None, top_level: false };
+
+        let first = get_comment_page(&listing, CommentSort::Oldest, None, 2).await.unwrap();
+        let cursor = first.next_cursor.clone().unwrap();
+        let rest = get_comment_page(&listing, CommentSort::Oldest, Some(&cursor), 2).await.unwrap();
+        let contents: Vec<_> = first.comments.iter().chain(&rest.comments).map(|c| c.content.as_str()).collect();
+        assert_eq!(contents, reviews);
+        assert!(rest.next_cursor.is_none());
+
+        let worst = get_comment_page(&listing, CommentSort::MostNegative, None, 1).await.unwrap();
+        assert_eq!(worst.comments[0].content, reviews[0]);
+        assert!(matches!(
+            get_comment_page(&listing, CommentSort::Newest, Some(&cursor), 2).await,
+            Err(CommentError::Validation(_))
+        ));
+
+        let negative = Listing::Post { post_id: post, status: Some(1), top_level: false };
+        assert_eq!(get_comment_page(&negative, CommentSort::Newest, None, 0).await.unwrap().comments.len(), 1);
     }
 
-    Ok(comments)
+    #[tokio::test]
+    async fn test_replies_form_threads() {
+        let post = bson::oid::ObjectId::new().to_hex();
+        let words = ["soup", "bread", "wine", "salad", "cheese", "dessert"];
+        let mut parent = None;
+        let mut ids = Vec::new();
+        for (depth, word) in words.into_iter().enumerate() {
+            let author = format!("G{}{}", depth, post);
+            let result = create_comment(&post, &author, &format!("About the {}", word), parent.clone()).await;
+            if depth as u8 > MAX_DEPTH {
+                assert!(matches!(result, Err(CommentError::Validation(_))));
+                break;
+            }
+            let comment = result.unwrap();
+            assert_eq!(comment.depth, depth as u8);
+            parent = comment.id.clone();
+            ids.push(comment.id.unwrap());
+        }
+
+        let other_post = bson::oid::ObjectId::new().to_hex();
+        let elsewhere = create_comment(&other_post, "G", "Nice", Some(ids[0].clone())).await;
+        assert!(matches!(elsewhere, Err(CommentError::Validation(_))));
+
+        let page = get_thread_page(&post, None, CommentSort::Newest, None, 0).await.unwrap();
+        assert_eq!(page.threads.len(), 1);
+        assert_eq!(page.threads[0].reply_count, MAX_DEPTH as u32);
+        let thread = get_thread(&ids[1]).await.unwrap();
+        assert_eq!(thread.flatten().len(), MAX_DEPTH as usize);
+
+        soft_delete_comment(&ids[2]).await.unwrap();
+        assert_eq!(get_thread(&ids[0]).await.unwrap().reply_count, 1);
+    }
 }
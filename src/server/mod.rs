@@ -1,4 +1,14 @@
+pub mod admin;
 pub mod db;
 pub mod comment_crud;
+pub mod reanalysis;
+pub mod review;
+pub mod redaction;
 pub mod sentiment;
 pub mod soroban;
+pub mod spam;
+pub mod store;
+pub mod summary;
+pub mod tfidf;
+pub mod topics;
+pub mod toxicity;
@@ -0,0 +1,220 @@
+//! Bulk re-analysis of stored comments after a lexicon or model change.
+
+use std::sync::Mutex;
+use crate::model::{Comment, CommentError, ReanalysisReport, SentimentTerm};
+use super::comment_crud;
+use super::sentiment::{self, Analysis};
+use super::soroban;
+
+/// Comments fetched per batch unless the caller asks otherwise.
+pub const DEFAULT_BATCH_SIZE: i64 = 100;
+
+/// What a re-analysis run should do.
+#[derive(Debug, Clone, Copy)]
+pub struct ReanalysisOptions {
+    pub batch_size: i64,
+    /// Re-run comments already scored by the current analyzer version.
+    pub force: bool,
+    /// Send comments that became negative to the Soroban sampler.
+    pub resubmit_negative: bool,
+}
+
+impl Default for ReanalysisOptions {
+    fn default() -> Self {
+        ReanalysisOptions { batch_size: DEFAULT_BATCH_SIZE, force: false, resubmit_negative: false }
+    }
+}
+
+/// Report of the current or last background run started by `start`.
+static JOB: Mutex<Option<ReanalysisReport>> = Mutex::new(None);
+
+/// Whether `analysis` gives `comment` a different automatic label,
+/// counting a switch between analyzed and unanalyzed as a change.
+fn label_changed(comment: &Comment, analysis: &Analysis) -> bool {
+    let was_analyzed = !comment.unanalyzed;
+    comment.auto_status != analysis.result.status() || was_analyzed != analysis.is_analyzed()
+}
+
+/// The comment's status once `analysis` is written: a human label wins.
+fn effective_status(comment: &Comment, analysis: &Analysis) -> u8 {
+    comment.human_status.unwrap_or(analysis.result.status())
+}
+
+/// Whether the stored analysis of `comment` is out of date.
+fn is_stale(comment: &Comment, analysis: &Analysis) -> bool {
+    comment.analyzer_version != analysis.analyzer_version || label_changed(comment, analysis)
+}
+
+/// `comment` as stored once `analysis` is written.
+fn reanalyzed(comment: &Comment, analysis: &Analysis) -> Comment {
+    Comment {
+        status: effective_status(comment, analysis),
+        auto_status: analysis.result.status(),
+        confidence: analysis.result.confidence,
+        scoring: analysis.result.score,
+        unanalyzed: !analysis.is_analyzed(),
+        evidence: analysis.result.evidence.iter().map(SentimentTerm::from).collect(),
+        ..comment.clone()
+    }
+}
+
+/// Fold one re-analyzed comment into the report. Returns whether it
+/// became negative and should be resubmitted.
+fn record(report: &mut ReanalysisReport, comment: &Comment, analysis: &Analysis) -> bool {
+    report.updated += 1;
+    if label_changed(comment, analysis) {
+        report.labels_changed += 1;
+    }
+    let status = effective_status(comment, analysis);
+    let newly_negative = !comment.is_deleted && comment.status != 1 && status == 1;
+    if newly_negative {
+        report.newly_negative += 1;
+    }
+    newly_negative
+}
+
+/// Re-run sentiment analysis over every comment in batches of
+/// `options.batch_size`, rewriting comments whose analyzer version or label
+/// is out of date. `on_batch` sees the running report after each batch.
+pub async fn run(options: ReanalysisOptions, mut on_batch: impl FnMut(&ReanalysisReport)) -> ReanalysisReport {
+    let mut report = ReanalysisReport::default();
+    let mut after: Option<String> = None;
+
+    loop {
+        let batch = match comment_crud::get_comments_after(after.as_deref(), options.batch_size.max(1)).await {
+            Ok(batch) => batch,
+            Err(e) => {
+                report.failure = Some(e.into());
+                break;
+            }
+        };
+        if batch.is_empty() {
+            break;
+        }
+
+        for comment in &batch {
+            report.scanned += 1;
+            let Some(id) = comment.id.as_deref() else { continue };
+
+            let analysis = sentiment::analyze(&comment.content);
+            if !options.force && !is_stale(comment, &analysis) {
+                continue;
+            }
+            if let Err(e) = comment_crud::set_analysis(id, &comment.post_id, &analysis, comment.human_status).await {
+                log::error!("Re-analysis of {} failed: {}", id, e);
+                report.errors += 1;
+                continue;
+            }
+
+            // Abuse that only reads as negative stays off-chain
+            let newly_negative = record(&mut report, comment, &analysis);
+            if newly_negative && options.resubmit_negative && reanalyzed(comment, &analysis).is_negative_sample() {
+                soroban::submit_negative_comment(id.to_string(), analysis.result.score as u32, comment.content.clone());
+                report.resubmitted += 1;
+            }
+        }
+
+        after = batch.last().and_then(|c| c.id.clone());
+        on_batch(&report);
+    }
+
+    report.finished = true;
+    on_batch(&report);
+    report
+}
+
+/// Start a run in the background. Fails if one is already in progress.
+pub fn start(options: ReanalysisOptions) -> Result<(), CommentError> {
+    {
+        let mut job = JOB.lock().map_err(|_| CommentError::Storage("Re-analysis state poisoned".to_string()))?;
+        if job.as_ref().is_some_and(|r| !r.finished) {
+            return Err(CommentError::Conflict("A re-analysis is already running".to_string()));
+        }
+        *job = Some(ReanalysisReport::default());
+    }
+
+    tokio::spawn(async move {
+        let report = run(options, |report| {
+            if let Ok(mut job) = JOB.lock() {
+                *job = Some(report.clone());
+            }
+        })
+        .await;
+        log::info!(
+            "Re-analysis finished: {} scanned, {} updated, {} labels changed, {} newly negative",
+            report.scanned,
+            report.updated,
+            report.labels_changed,
+            report.newly_negative
+        );
+    });
+    Ok(())
+}
+
+/// Report of the current or last background run, if any.
+pub fn status() -> Option<ReanalysisReport> {
+    JOB.lock().ok().and_then(|job| job.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comment(status: u8, version: Option<&str>) -> Comment {
+        Comment {
+            id: Some("0".repeat(24)),
+            status,
+            auto_status: status,
+            language: Some("en".to_string()),
+            analyzer_version: version.map(str::to_string),
+            ..Comment::sample("The soup was cold")
+        }
+    }
+
+    #[test]
+    fn test_stale_when_version_or_label_differs() {
+        let analysis = sentiment::analyze("The soup was cold");
+        let current = analysis.analyzer_version.as_deref();
+        assert!(!is_stale(&comment(1, current), &analysis));
+        assert!(is_stale(&comment(1, None), &analysis));
+        assert!(is_stale(&comment(2, current), &analysis));
+    }
+
+    #[test]
+    fn test_abuse_is_not_resubmitted() {
+        let analysis = sentiment::analyze("I hate you");
+        let mut abusive = comment(2, None);
+        abusive.toxic_terms = crate::server::toxicity::classifier().classify("I hate you").terms;
+        assert_eq!(reanalyzed(&abusive, &analysis).status, 1);
+        assert!(!reanalyzed(&abusive, &analysis).is_negative_sample());
+        assert!(reanalyzed(&comment(2, None), &sentiment::analyze("The soup was cold")).is_negative_sample());
+    }
+
+    #[test]
+    fn test_record_counts_newly_negative() {
+        let analysis = sentiment::analyze("The soup was cold");
+        let mut report = ReanalysisReport::default();
+
+        assert!(record(&mut report, &comment(2, None), &analysis));
+        assert!(!record(&mut report, &comment(1, None), &analysis));
+        let mut deleted = comment(3, None);
+        deleted.is_deleted = true;
+        assert!(!record(&mut report, &deleted, &analysis));
+
+        assert_eq!((report.updated, report.labels_changed, report.newly_negative), (3, 2, 1));
+    }
+
+    #[test]
+    fn test_human_label_survives_reanalysis() {
+        let analysis = sentiment::analyze("The soup was cold");
+        let mut labelled = comment(3, analysis.analyzer_version.as_deref());
+        labelled.auto_status = 1;
+        labelled.human_status = Some(3);
+
+        // the analyzer agrees with its earlier self: nothing to rewrite
+        assert!(!is_stale(&labelled, &analysis));
+        let after = reanalyzed(&labelled, &analysis);
+        assert_eq!((after.status, after.auto_status), (3, 1));
+        assert!(!record(&mut ReanalysisReport::default(), &labelled, &analysis));
+    }
+}
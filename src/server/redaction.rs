@@ -0,0 +1,423 @@
+//! Masking of personal data in review text.
+//!
+//! Emails, phone numbers, IBANs and card-like numbers are replaced with a
+//! placeholder ("[email]") before a comment is analyzed, stored or sent
+//! on-chain. What happens to the original is set by `PII_ORIGINAL`:
+//! `discard` (default) keeps nothing, `encrypt` stores it sealed with
+//! AES-256-GCM under `PII_ENCRYPTION_KEY` (32 bytes, base64).
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use once_cell::sync::OnceCell;
+
+/// Digits in a number that make it a phone number.
+const PHONE_DIGITS: std::ops::RangeInclusive<usize> = 9..=15;
+/// Digits in a payment card number.
+const CARD_DIGITS: std::ops::RangeInclusive<usize> = 13..=19;
+/// Letters and digits in an IBAN, country code included.
+const IBAN_CHARS: std::ops::RangeInclusive<usize> = 15..=34;
+/// Separators allowed inside phone and card numbers.
+const NUMBER_SEPARATORS: &[char] = &[' ', '-', '.', '(', ')', '/'];
+
+/// Kind of personal data found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pii {
+    Email,
+    Phone,
+    Card,
+    Iban,
+}
+
+impl Pii {
+    pub fn placeholder(self) -> &'static str {
+        match self {
+            Pii::Email => "[email]",
+            Pii::Phone => "[phone]",
+            Pii::Card => "[card]",
+            Pii::Iban => "[iban]",
+        }
+    }
+}
+
+/// Text with personal data masked.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Redaction {
+    pub text: String,
+    /// What was masked, in text order.
+    pub found: Vec<Pii>,
+}
+
+impl Redaction {
+    pub fn is_redacted(&self) -> bool {
+        !self.found.is_empty()
+    }
+}
+
+/// Characters besides whitespace that separate words ("IBAN:DE89...").
+const WORD_SEPARATORS: &[char] = &[':', ';', ',', '(', ')', '<', '>', '"'];
+
+/// Words with their byte offsets.
+fn words(text: &str) -> Vec<(usize, &str)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (c.is_whitespace() || WORD_SEPARATORS.contains(&c), start) {
+            (true, Some(s)) => {
+                words.push((s, &text[s..i]));
+                start = None;
+            }
+            (false, None) => start = Some(i),
+            _ => {}
+        }
+    }
+    words
+}
+
+/// Byte range of `word` (at `offset`) without leading and trailing
+/// punctuation.
+fn trimmed(offset: usize, word: &str) -> (usize, usize) {
+    let inner = word.trim_start_matches(|c: char| !c.is_alphanumeric());
+    let start = offset + word.len() - inner.len();
+    let inner = inner.trim_end_matches(|c: char| !c.is_alphanumeric());
+    (start, start + inner.len())
+}
+
+fn is_email(word: &str) -> bool {
+    let Some((local, domain)) = word.split_once('@') else { return false };
+    !local.is_empty()
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && domain.chars().all(|c| c.is_alphanumeric() || c == '.' || c == '-')
+}
+
+fn emails(text: &str) -> Vec<(usize, usize, Pii)> {
+    words(text)
+        .into_iter()
+        .map(|(offset, word)| trimmed(offset, word))
+        .filter(|(start, end)| is_email(&text[*start..*end]))
+        .map(|(start, end)| (start, end, Pii::Email))
+        .collect()
+}
+
+/// IBANs, written solid ("TR330006100519786457841326") or in groups of
+/// four: a word starting with an uppercase country code and two check
+/// digits, followed by short alphanumeric groups.
+fn ibans(text: &str) -> Vec<(usize, usize, Pii)> {
+    let words: Vec<(usize, usize)> = words(text).into_iter().map(|(offset, word)| trimmed(offset, word)).collect();
+    let mut found = Vec::new();
+    let mut i = 0;
+    while i < words.len() {
+        let (start, end) = words[i];
+        let head: Vec<char> = text[start..end].chars().collect();
+        let is_head = head.len() >= 4
+            && head[..2].iter().all(|c| c.is_ascii_uppercase())
+            && head[2..4].iter().all(|c| c.is_ascii_digit())
+            && head.iter().all(|c| c.is_ascii_alphanumeric());
+        if !is_head {
+            i += 1;
+            continue;
+        }
+
+        let mut chars = head.len();
+        let mut last = i;
+        while last + 1 < words.len() && chars < *IBAN_CHARS.end() {
+            let (s, e) = words[last + 1];
+            let group = &text[s..e];
+            if group.len() > 4 || !group.chars().all(|c| c.is_ascii_digit() || c.is_ascii_uppercase()) {
+                break;
+            }
+            chars += group.len();
+            last += 1;
+        }
+        if IBAN_CHARS.contains(&chars) {
+            found.push((start, words[last].1, Pii::Iban));
+            i = last + 1;
+        } else {
+            i += 1;
+        }
+    }
+    found
+}
+
+/// Luhn checksum, as on payment cards.
+fn luhn(digits: &[u32]) -> bool {
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, d)| if i % 2 == 1 { if d * 2 > 9 { d * 2 - 9 } else { d * 2 } } else { *d })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+/// A date or time written with one separator: "12.10.2026", "12/10/26",
+/// "2026-10-12", "19.30".
+fn is_date_or_time(group: &str) -> bool {
+    let Some(separator) = group.chars().find(|c| !c.is_ascii_digit()) else { return false };
+    if !matches!(separator, '.' | '/' | '-') {
+        return false;
+    }
+    let parts: Vec<&str> = group.split(separator).collect();
+    if !parts.iter().all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit())) {
+        return false;
+    }
+    let value = |p: &str| p.parse::<u32>().unwrap_or(u32::MAX);
+    match parts[..] {
+        [d, m, y] if d.len() <= 2 && m.len() <= 2 && matches!(y.len(), 2 | 4) => {
+            (1..=31).contains(&value(d)) && (1..=12).contains(&value(m))
+        }
+        [y, m, d] if y.len() == 4 && m.len() <= 2 && d.len() <= 2 => {
+            (1..=12).contains(&value(m)) && (1..=31).contains(&value(d))
+        }
+        [h, m] if separator == '.' && h.len() <= 2 && m.len() == 2 => value(h) <= 23 && value(m) <= 59,
+        _ => false,
+    }
+}
+
+/// `run` without its last space-separated group if that is a date or
+/// time. `group` is where that group starts and how many of `digits` come
+/// before it; the group's digits are dropped.
+fn without_date(
+    text: &str,
+    run: Option<(usize, usize)>,
+    group: Option<(usize, usize)>,
+    digits: &mut Vec<u32>,
+) -> Option<(usize, usize)> {
+    let (Some((start, end)), Some((group_start, before))) = (run, group) else { return run };
+    if !is_date_or_time(&text[group_start..end]) {
+        return run;
+    }
+    digits.truncate(before);
+    let end = start + text[start..group_start].trim_end_matches(|c: char| !c.is_ascii_digit()).len();
+    (before > 0).then_some((start, end))
+}
+
+/// Runs of digits and separators long enough to be a phone or card number.
+/// Dates and times end a run, so "12.10.2026 19.30" is neither.
+fn numbers(text: &str) -> Vec<(usize, usize, Pii)> {
+    let mut found = Vec::new();
+    let mut run: Option<(usize, usize)> = None;
+    // Start of the run's current space-separated group, and the digits
+    // before it
+    let mut group: Option<(usize, usize)> = None;
+    let mut digits: Vec<u32> = Vec::new();
+
+    let mut close = |run: Option<(usize, usize)>, digits: &mut Vec<u32>| {
+        if let Some((start, end)) = run {
+            let kind = if CARD_DIGITS.contains(&digits.len()) && luhn(digits) {
+                Some(Pii::Card)
+            } else if PHONE_DIGITS.contains(&digits.len()) {
+                Some(Pii::Phone)
+            } else {
+                None
+            };
+            if let Some(kind) = kind {
+                found.push((start, end, kind));
+            }
+        }
+        digits.clear();
+    };
+
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    for (n, (i, c)) in chars.iter().enumerate() {
+        if c.is_ascii_digit() {
+            let start = match run {
+                Some((start, _)) => start,
+                // Include a leading "+" of an international number
+                None if n > 0 && chars[n - 1].1 == '+' => chars[n - 1].0,
+                None => *i,
+            };
+            if run.is_none() || group.is_none() {
+                group = Some((*i, digits.len()));
+            }
+            run = Some((start, i + 1));
+            digits.push(*c as u32 - '0' as u32);
+        } else if run.is_some() && *c == ' ' {
+            let rest = without_date(text, run, group.take(), &mut digits);
+            if rest != run {
+                close(rest, &mut digits);
+                run = None;
+            }
+        } else if run.is_some() && NUMBER_SEPARATORS.contains(c) {
+            continue;
+        } else {
+            close(without_date(text, run.take(), group.take(), &mut digits), &mut digits);
+        }
+    }
+    close(without_date(text, run.take(), group.take(), &mut digits), &mut digits);
+    found
+}
+
+/// Byte ranges of the phone numbers in `text`. The one phone detector:
+/// spam scoring counts what redaction masks.
+pub fn find_phone_numbers(text: &str) -> Vec<(usize, usize)> {
+    numbers(text)
+        .into_iter()
+        .filter(|(_, _, kind)| *kind == Pii::Phone)
+        .map(|(start, end, _)| (start, end))
+        .collect()
+}
+
+/// Mask emails, IBANs, card numbers and phone numbers in `text`.
+pub fn redact(text: &str) -> Redaction {
+    let mut spans: Vec<(usize, usize, Pii)> = emails(text);
+    let mut add = |more: Vec<(usize, usize, Pii)>| {
+        for span in more {
+            if !spans.iter().any(|(s, e, _)| *s < span.1 && span.0 < *e) {
+                spans.push(span);
+            }
+        }
+    };
+    add(ibans(text));
+    add(numbers(text));
+    spans.sort_by_key(|(start, _, _)| *start);
+
+    let mut redacted = String::with_capacity(text.len());
+    let mut pos = 0;
+    for (start, end, kind) in &spans {
+        redacted.push_str(&text[pos..*start]);
+        redacted.push_str(kind.placeholder());
+        pos = *end;
+    }
+    redacted.push_str(&text[pos..]);
+
+    Redaction { text: redacted, found: spans.into_iter().map(|(_, _, kind)| kind).collect() }
+}
+
+/// What to keep of text that had personal data masked.
+#[derive(Clone)]
+pub enum OriginalPolicy {
+    Discard,
+    Encrypt(Box<Aes256Gcm>),
+}
+
+impl OriginalPolicy {
+    /// Read `PII_ORIGINAL` and, for `encrypt`, `PII_ENCRYPTION_KEY`.
+    pub fn from_env() -> Result<Self, String> {
+        match std::env::var("PII_ORIGINAL").unwrap_or_default().trim() {
+            "" | "discard" => Ok(OriginalPolicy::Discard),
+            "encrypt" => {
+                let key = std::env::var("PII_ENCRYPTION_KEY").unwrap_or_default();
+                OriginalPolicy::with_key(key.trim())
+            }
+            other => Err(format!("Invalid PII_ORIGINAL: {} (expected discard or encrypt)", other)),
+        }
+    }
+
+    /// Encrypt with a base64-encoded 32-byte key.
+    pub fn with_key(key: &str) -> Result<Self, String> {
+        let bytes = BASE64.decode(key).map_err(|_| "PII_ENCRYPTION_KEY is not valid base64".to_string())?;
+        if bytes.len() != 32 {
+            return Err("PII_ENCRYPTION_KEY must be 32 bytes".to_string());
+        }
+        Ok(OriginalPolicy::Encrypt(Box::new(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&bytes)))))
+    }
+
+    /// The original to store next to the redacted text: base64 of nonce
+    /// and ciphertext, or nothing.
+    pub fn seal(&self, original: &str) -> Result<Option<String>, String> {
+        match self {
+            OriginalPolicy::Discard => Ok(None),
+            OriginalPolicy::Encrypt(cipher) => {
+                let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+                let ciphertext =
+                    cipher.encrypt(&nonce, original.as_bytes()).map_err(|_| "Cannot encrypt original".to_string())?;
+                let mut sealed = nonce.to_vec();
+                sealed.extend(ciphertext);
+                Ok(Some(BASE64.encode(sealed)))
+            }
+        }
+    }
+
+    /// Recover an original sealed by `seal`.
+    pub fn open(&self, sealed: &str) -> Result<String, String> {
+        let OriginalPolicy::Encrypt(cipher) = self else {
+            return Err("Originals are not kept (PII_ORIGINAL=discard)".to_string());
+        };
+        let bytes = BASE64.decode(sealed).map_err(|_| "Sealed original is not valid base64".to_string())?;
+        if bytes.len() < 12 {
+            return Err("Sealed original is too short".to_string());
+        }
+        let (nonce, ciphertext) = bytes.split_at(12);
+        let plain = cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| "Cannot decrypt original (wrong key?)".to_string())?;
+        String::from_utf8(plain).map_err(|_| "Decrypted original is not UTF-8".to_string())
+    }
+}
+
+static POLICY: OnceCell<OriginalPolicy> = OnceCell::new();
+
+/// Read the original-text policy. Call once at startup; a bad setting or
+/// key stops the server.
+pub fn init_redaction() -> Result<(), String> {
+    let policy = OriginalPolicy::from_env()?;
+    log::info!(
+        "PII originals: {}",
+        if matches!(policy, OriginalPolicy::Encrypt(_)) { "encrypted" } else { "discarded" }
+    );
+    POLICY.set(policy).map_err(|_| "Redaction already initialized".to_string())
+}
+
+/// The configured policy; discards originals if `init_redaction()` was
+/// never called.
+pub fn policy() -> &'static OriginalPolicy {
+    POLICY.get_or_init(|| OriginalPolicy::Discard)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redacts_contact_details() {
+        let redaction = redact("Mail me at jane.doe+food@example.co.uk or call +90 (555) 123-45-67.");
+        assert_eq!(redaction.text, "Mail me at [email] or call [phone].");
+        assert_eq!(redaction.found, [Pii::Email, Pii::Phone]);
+    }
+
+    #[test]
+    fn test_redacts_payment_details() {
+        let redaction = redact("Charged twice on 4111 1111 1111 1111, refund to TR33 0006 1005 1978 6457 8413 26 please");
+        assert_eq!(redaction.text, "Charged twice on [card], refund to [iban] please");
+        assert_eq!(redact("IBAN:DE89370400440532013000").text, "IBAN:[iban]");
+    }
+
+    #[test]
+    fn test_leaves_ordinary_numbers() {
+        for text in ["Table for 4 at 19.30 on 12.10.2026", "Paid €145.50 for 2", "Room 101, TR33 is a road"] {
+            assert_eq!(redact(text).text, text);
+        }
+    }
+
+    #[test]
+    fn test_dates_and_times_are_not_numbers() {
+        for text in [
+            "Booked for 12.10.2026 19.30, arrived 19.45",
+            "Stayed 12.10.2026 - 13.10.2026",
+            "From 2026-10-12 to 14/10/26",
+            "Order 1234 5678 9012 3456 came late",
+        ] {
+            assert_eq!(redact(text).text, text);
+        }
+        assert_eq!(redact("On 12.10.2026 call 0555 123 45 67").text, "On 12.10.2026 call [phone]");
+        assert_eq!(redact("12.10.2026 0555 123 45 67").text, "12.10.2026 [phone]");
+        assert_eq!(redact("Call 0555 123 45 67 19.30").text, "Call [phone] 19.30");
+    }
+
+    #[test]
+    fn test_seal_and_open() {
+        let policy = OriginalPolicy::with_key(&BASE64.encode([7u8; 32])).unwrap();
+        let sealed = policy.seal("call 0555 123 45 67").unwrap().unwrap();
+        assert!(!sealed.contains("0555"));
+        assert_eq!(policy.open(&sealed).unwrap(), "call 0555 123 45 67");
+
+        let other = OriginalPolicy::with_key(&BASE64.encode([8u8; 32])).unwrap();
+        assert!(other.open(&sealed).is_err());
+        assert_eq!(OriginalPolicy::Discard.seal("x").unwrap(), None);
+        assert!(OriginalPolicy::with_key("c2hvcnQ=").is_err());
+    }
+}
@@ -0,0 +1,61 @@
+//! Routing of uncertain sentiment labels to human review.
+//!
+//! A comment whose analysis has a confidence below the threshold — mixed
+//! evidence, or a weak lean either way — is flagged `needs_review` and
+//! listed in the review queue, where a moderator sets the final label.
+//! Comments without any sentiment evidence are confidently neutral and are
+//! not flagged.
+
+use once_cell::sync::OnceCell;
+use super::sentiment::Analysis;
+
+/// Below this confidence a label is sent for review.
+pub const DEFAULT_REVIEW_CONFIDENCE: f32 = 0.4;
+
+static THRESHOLD: OnceCell<f32> = OnceCell::new();
+
+/// Read `SENTIMENT_REVIEW_CONFIDENCE` (0.0–1.0; 0 switches review off).
+/// Call once at startup.
+pub fn init_review() -> Result<(), String> {
+    let threshold = match std::env::var("SENTIMENT_REVIEW_CONFIDENCE") {
+        Ok(value) if !value.trim().is_empty() => value
+            .trim()
+            .parse::<f32>()
+            .ok()
+            .filter(|t| (0.0..=1.0).contains(t))
+            .ok_or_else(|| format!("Invalid SENTIMENT_REVIEW_CONFIDENCE: {}", value))?,
+        _ => DEFAULT_REVIEW_CONFIDENCE,
+    };
+    log::info!("Sentiment labels below confidence {} go to human review", threshold);
+
+    THRESHOLD
+        .set(threshold)
+        .map_err(|_| "Review threshold already initialized".to_string())
+}
+
+/// The configured threshold, or the default if `init_review()` was never
+/// called.
+pub fn threshold() -> f32 {
+    *THRESHOLD.get_or_init(|| DEFAULT_REVIEW_CONFIDENCE)
+}
+
+/// Whether a comment analyzed as `analysis` should get a human label.
+/// Text no analyzer could read is not guessed at, so there is nothing to
+/// review.
+pub fn needs_review(analysis: &Analysis) -> bool {
+    analysis.is_analyzed() && analysis.result.confidence < threshold()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::sentiment::analyze;
+
+    #[test]
+    fn test_only_uncertain_labels_need_review() {
+        assert!(!needs_review(&analyze("I had dinner here last night")));
+        assert!(!needs_review(&analyze("The soup was cold and the waiter was rude")));
+        assert!(needs_review(&analyze("The soup was good. The bread was bad.")));
+        assert!(!needs_review(&analyze("Lorem ipsum dolor sit amet")));
+    }
+}
@@ -0,0 +1,370 @@
+//! Multinomial Naive Bayes sentiment model.
+//!
+//! Trained offline from a labeled JSONL file (see `dene-admin train`),
+//! saved as JSON next to the app and loaded at startup. Everything runs on
+//! the CPU from the model file; no network access is involved.
+
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use super::keyword::{is_negated_contraction, NEGATION_SCOPE};
+use super::language::{self, Language};
+use super::lexicon::{self, Lexicon, Role};
+use super::tokenizer::tokenize;
+use super::{Evidence, SentimentAnalyzer, SentimentLabel, SentimentResult};
+
+/// Prefix for words inside a negation scope, so "not good" and "good" are
+/// different features.
+const NEGATED_PREFIX: &str = "not_";
+
+/// Features whose positive/negative log-likelihood ratio is below this are
+/// not reported as evidence.
+const MIN_EVIDENCE_WEIGHT: f64 = 0.5;
+
+/// Where models live unless `SENTIMENT_MODEL_DIR` says otherwise.
+const DEFAULT_MODEL_DIR: &str = "data/models";
+
+/// One line of a training file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrainingExample {
+    pub text: String,
+    /// "negative", "neutral" or "positive".
+    pub label: String,
+    /// ISO 639-1 code; detected from the text when missing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+}
+
+impl TrainingExample {
+    pub fn label(&self) -> Option<SentimentLabel> {
+        SentimentLabel::parse(&self.label)
+    }
+
+    /// Language the example is written in, if known or detectable.
+    pub fn language(&self) -> Option<Language> {
+        match &self.language {
+            Some(code) => Language::from_code(code),
+            None => language::detect(&self.text),
+        }
+    }
+}
+
+/// Read a JSONL training file. Blank lines are skipped; any other line
+/// that is not a valid example is an error naming the line.
+pub fn read_examples(path: &Path) -> Result<Vec<TrainingExample>, String> {
+    let file = std::fs::File::open(path).map_err(|e| format!("Cannot open {}: {}", path.display(), e))?;
+
+    let mut examples = Vec::new();
+    for (n, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let example: TrainingExample = serde_json::from_str(&line)
+            .map_err(|e| format!("{}:{}: invalid example: {}", path.display(), n + 1, e))?;
+        if example.label().is_none() {
+            return Err(format!("{}:{}: unknown label {:?}", path.display(), n + 1, example.label));
+        }
+        examples.push(example);
+    }
+    Ok(examples)
+}
+
+/// A trained model for one language.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NaiveBayesModel {
+    /// ISO 639-1 code of the training data.
+    pub language: String,
+    /// Unix time of training, used as the model version.
+    #[serde(default)]
+    pub trained_at: u64,
+    /// Negators and post-negators taken from the lexicon at training time,
+    /// so the model extracts the same features wherever it is loaded.
+    negators: Vec<String>,
+    post_negators: Vec<String>,
+    /// Training documents per label, indexed `status - 1`.
+    doc_counts: [u32; 3],
+    /// Occurrences of each feature per label.
+    word_counts: HashMap<String, [u32; 3]>,
+    /// Feature occurrences per label.
+    total_words: [u64; 3],
+}
+
+impl NaiveBayesModel {
+    /// Train on the examples written in `language`; examples in other
+    /// languages are ignored.
+    pub fn train(language: Language, examples: &[TrainingExample], lexicon: &Lexicon) -> Result<Self, String> {
+        let words_with = |role: Role| -> Vec<String> {
+            lexicon
+                .roles
+                .iter()
+                .filter(|(term, r, _)| *r == role && !term.contains(' '))
+                .map(|(term, _, _)| term.to_lowercase())
+                .collect()
+        };
+
+        let mut model = NaiveBayesModel {
+            language: language.code().to_string(),
+            trained_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+            negators: words_with(Role::Negator),
+            post_negators: words_with(Role::PostNegator),
+            doc_counts: [0; 3],
+            word_counts: HashMap::new(),
+            total_words: [0; 3],
+        };
+
+        for example in examples.iter().filter(|e| e.language() == Some(language)) {
+            let label = example.label().ok_or_else(|| format!("Unknown label {:?}", example.label))?;
+            let class = label.status() as usize - 1;
+            model.doc_counts[class] += 1;
+            for (feature, _) in model.features(&example.text) {
+                model.word_counts.entry(feature).or_default()[class] += 1;
+                model.total_words[class] += 1;
+            }
+        }
+
+        if model.doc_counts.iter().sum::<u32>() == 0 {
+            return Err(format!("No training examples for language {}", language.code()));
+        }
+        Ok(model)
+    }
+
+    /// Parse a saved model.
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let model: NaiveBayesModel =
+            serde_json::from_str(source).map_err(|e| format!("Invalid sentiment model: {}", e))?;
+        if Language::from_code(&model.language).is_none() {
+            return Err(format!("Unsupported model language: {}", model.language));
+        }
+        if model.doc_counts.iter().sum::<u32>() == 0 {
+            return Err("Sentiment model has no training documents".to_string());
+        }
+        Ok(model)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        Self::parse(&source).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+        }
+        let json = serde_json::to_string(self).map_err(|e| format!("Cannot serialize model: {}", e))?;
+        std::fs::write(path, json).map_err(|e| format!("Cannot write {}: {}", path.display(), e))
+    }
+
+    /// Number of training documents.
+    pub fn documents(&self) -> u32 {
+        self.doc_counts.iter().sum()
+    }
+
+    /// Number of distinct features seen in training.
+    pub fn vocabulary(&self) -> usize {
+        self.word_counts.len()
+    }
+
+    /// Lowercased words and their byte ranges, with words in a negation
+    /// scope prefixed by `NEGATED_PREFIX`. Clause punctuation ends the scope.
+    fn features(&self, text: &str) -> Vec<(String, Range<usize>)> {
+        let negators: HashSet<&str> = self.negators.iter().map(String::as_str).collect();
+        let post_negators: HashSet<&str> = self.post_negators.iter().map(String::as_str).collect();
+
+        let mut features: Vec<(String, Range<usize>)> = Vec::new();
+        let mut negated_until = 0;
+        for (i, token) in tokenize(text).into_iter().enumerate() {
+            if token.boundary_before {
+                negated_until = 0;
+            }
+            let span = token.start..token.end;
+            if negators.contains(token.text.as_str()) || is_negated_contraction(&token.text) {
+                negated_until = i + 1 + NEGATION_SCOPE;
+                features.push((token.text, span));
+            } else if post_negators.contains(token.text.as_str()) && !token.boundary_before {
+                if let Some((last, _)) = features.last_mut().filter(|(f, _)| !f.starts_with(NEGATED_PREFIX)) {
+                    *last = format!("{}{}", NEGATED_PREFIX, last);
+                }
+            } else if i < negated_until {
+                features.push((format!("{}{}", NEGATED_PREFIX, token.text), span));
+            } else {
+                features.push((token.text, span));
+            }
+        }
+        features
+    }
+
+    /// Laplace-smoothed log P(feature | label) for each label.
+    fn log_likelihoods(&self, feature: &str) -> [f64; 3] {
+        let counts = self.word_counts.get(feature).copied().unwrap_or_default();
+        let vocabulary = self.word_counts.len() as f64 + 1.0;
+        std::array::from_fn(|c| ((counts[c] as f64 + 1.0) / (self.total_words[c] as f64 + vocabulary)).ln())
+    }
+}
+
+impl SentimentAnalyzer for NaiveBayesModel {
+    fn name(&self) -> &'static str {
+        "naive_bayes"
+    }
+
+    fn version(&self) -> String {
+        format!("naive_bayes/{}/{}", self.language, self.trained_at)
+    }
+
+    fn analyze(&self, text: &str) -> SentimentResult {
+        let features = self.features(text);
+        let documents = self.documents() as f64;
+
+        let mut log_posterior: [f64; 3] =
+            std::array::from_fn(|c| ((self.doc_counts[c] as f64 + 1.0) / (documents + 3.0)).ln());
+        let mut evidence: Vec<Evidence> = Vec::new();
+        for (feature, span) in features {
+            let likelihoods = self.log_likelihoods(&feature);
+            for (posterior, likelihood) in log_posterior.iter_mut().zip(likelihoods) {
+                *posterior += likelihood;
+            }
+
+            // Unseen words carry no information.
+            let weight = likelihoods[2] - likelihoods[0];
+            if self.word_counts.contains_key(&feature) && weight.abs() >= MIN_EVIDENCE_WEIGHT {
+                evidence.push(Evidence { term: feature, weight, aspect: None, start: span.start, end: span.end });
+            }
+        }
+
+        // Softmax over the log posteriors.
+        let max = log_posterior.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let exp: Vec<f64> = log_posterior.iter().map(|p| (p - max).exp()).collect();
+        let total: f64 = exp.iter().sum();
+        let probability: Vec<f64> = exp.iter().map(|e| e / total).collect();
+
+        let best = (0..3).max_by(|a, b| probability[*a].total_cmp(&probability[*b])).unwrap_or(1);
+        let score = (50.0 + (probability[2] - probability[0]) * 50.0).round().clamp(0.0, 100.0) as u8;
+
+        SentimentResult {
+            label: SentimentLabel::ALL[best],
+            score,
+            confidence: probability[best] as f32,
+            evidence,
+        }
+    }
+}
+
+/// Directory holding `<code>.json` models: `SENTIMENT_MODEL_DIR`, or
+/// `data/models`.
+pub fn model_dir() -> PathBuf {
+    std::env::var("SENTIMENT_MODEL_DIR")
+        .ok()
+        .filter(|d| !d.trim().is_empty())
+        .map_or_else(|| PathBuf::from(DEFAULT_MODEL_DIR), |d| PathBuf::from(d.trim()))
+}
+
+/// Path of the model for one language.
+pub fn model_path(dir: &Path, language: Language) -> PathBuf {
+    dir.join(format!("{}.json", language.code()))
+}
+
+/// Load the model for `language` from `dir`. `Ok(None)` means there is no
+/// model file; an unreadable or invalid file is an error.
+pub fn load_model(dir: &Path, language: Language) -> Result<Option<NaiveBayesModel>, String> {
+    let path = model_path(dir, language);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let model = NaiveBayesModel::load(&path)?;
+    if model.language != language.code() {
+        return Err(format!("{}: model is for language {:?}", path.display(), model.language));
+    }
+    Ok(Some(model))
+}
+
+/// Train a model for `language` against the shared lexicon.
+pub fn train(language: Language, examples: &[TrainingExample]) -> Result<NaiveBayesModel, String> {
+    NaiveBayesModel::train(language, examples, &lexicon::store(language).current())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example(text: &str, label: &str) -> TrainingExample {
+        TrainingExample { text: text.to_string(), label: label.to_string(), language: Some("en".to_string()) }
+    }
+
+    fn trained() -> NaiveBayesModel {
+        let examples = [
+            example("The food was delicious and the staff lovely", "positive"),
+            example("Lovely evening, delicious dessert", "positive"),
+            example("Friendly staff and a lovely view", "positive"),
+            example("The soup was cold and the waiter rude", "negative"),
+            example("Cold food, rude staff, never again", "negative"),
+            example("The room was noisy and cold", "negative"),
+            example("We had dinner on Tuesday", "neutral"),
+            example("Ordered the set menu for two", "neutral"),
+        ];
+        NaiveBayesModel::train(Language::English, &examples, &Lexicon::builtin(Language::English)).unwrap()
+    }
+
+    #[test]
+    fn test_classifies_after_training() {
+        let model = trained();
+        assert_eq!(model.documents(), 8);
+        assert_eq!(model.analyze("Delicious food and lovely staff").label, SentimentLabel::Positive);
+        assert_eq!(model.analyze("Rude waiter and cold soup").label, SentimentLabel::Negative);
+
+        let result = model.analyze("Rude and cold");
+        assert!(result.score < 50);
+        assert!(result.confidence > 0.5);
+        assert!(result.evidence.iter().any(|e| e.term == "rude" && e.weight < 0.0));
+    }
+
+    #[test]
+    fn test_negation_features() {
+        let model = trained();
+        let words = |text: &str| -> Vec<String> { model.features(text).into_iter().map(|(f, _)| f).collect() };
+        assert_eq!(words("not lovely, cold"), ["not", "not_lovely", "cold"]);
+        assert_eq!(words("wasn't lovely"), ["wasn't", "not_lovely"]);
+        assert_eq!(model.features("wasn't lovely")[1].1, 7..13);
+    }
+
+    #[test]
+    fn test_ignores_other_languages() {
+        let examples = [example("Lovely", "positive"), TrainingExample {
+            text: "Harika".to_string(),
+            label: "positive".to_string(),
+            language: Some("tr".to_string()),
+        }];
+        let model = NaiveBayesModel::train(Language::English, &examples, &Lexicon::builtin(Language::English)).unwrap();
+        assert_eq!(model.documents(), 1);
+        assert!(NaiveBayesModel::train(Language::German, &examples, &Lexicon::builtin(Language::German)).is_err());
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let dir = std::env::temp_dir().join(format!("dene-model-{}", std::process::id()));
+        let model = trained();
+        model.save(&model_path(&dir, Language::English)).unwrap();
+
+        assert_eq!(load_model(&dir, Language::English).unwrap(), Some(model));
+        assert_eq!(load_model(&dir, Language::French).unwrap(), None);
+
+        std::fs::write(model_path(&dir, Language::French), "{}").unwrap();
+        assert!(load_model(&dir, Language::French).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_read_examples_reports_bad_lines() {
+        let path = std::env::temp_dir().join(format!("dene-train-{}.jsonl", std::process::id()));
+        std::fs::write(&path, "{\"text\": \"Great\", \"label\": \"positive\"}\n\n{\"text\": \"Meh\", \"label\": \"bad\"}\n").unwrap();
+        let err = read_examples(&path).unwrap_err();
+        assert!(err.contains(":3:"), "{}", err);
+
+        std::fs::write(&path, "{\"text\": \"Great\", \"label\": \"Positive\"}\n").unwrap();
+        assert_eq!(read_examples(&path).unwrap().len(), 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
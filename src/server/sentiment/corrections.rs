@@ -0,0 +1,150 @@
+//! Learning from moderators' label corrections.
+//!
+//! Comments a moderator labelled are exported as training examples (the
+//! same JSON lines as `data/eval/reviews.jsonl`), so the offline model can
+//! be retrained and the analyzers evaluated against them. Comments where
+//! the moderator disagreed with the analyzer also point at missing
+//! lexicon terms: a word that keeps turning up in comments corrected to
+//! negative, and rarely in ones corrected to positive, is suggested as a
+//! negative term.
+
+use std::collections::{BTreeMap, BTreeSet};
+use crate::model::Comment;
+use super::bayes::TrainingExample;
+use super::keyphrases;
+use super::language::{self, Language};
+use super::lexicon;
+use super::SentimentLabel;
+
+/// A word suggested for a lexicon.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TermSuggestion {
+    pub language: Language,
+    pub word: String,
+    /// Negative or positive.
+    pub label: SentimentLabel,
+    /// Corrected comments with that label containing the word.
+    pub corrections: u32,
+}
+
+fn language_of(comment: &Comment) -> Language {
+    comment
+        .language
+        .as_deref()
+        .and_then(Language::from_code)
+        .or_else(|| language::detect(&comment.content))
+        .unwrap_or(Language::English)
+}
+
+/// Training examples from the comments with a moderator's label.
+pub fn examples(comments: &[Comment]) -> Vec<TrainingExample> {
+    comments
+        .iter()
+        .filter_map(|comment| {
+            Some(TrainingExample {
+                text: comment.content.clone(),
+                label: SentimentLabel::from_status(comment.human_status?).as_str().to_string(),
+                language: comment.language.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Words missing from the lexicons that appear in at least `min_count`
+/// comments a moderator relabelled as negative (or positive), and in more
+/// than twice as many of those as of comments relabelled the other way.
+/// Most frequent first.
+pub fn suggest_terms(comments: &[Comment], min_count: u32) -> Vec<TermSuggestion> {
+    // (language, word) → corrections to negative, to positive
+    let mut counts: BTreeMap<(&'static str, String), [u32; 2]> = BTreeMap::new();
+    for comment in comments {
+        let label = match comment.human_status {
+            Some(status) if status != comment.auto_status => SentimentLabel::from_status(status),
+            _ => continue,
+        };
+        let side = match label {
+            SentimentLabel::Negative => 0,
+            SentimentLabel::Positive => 1,
+            SentimentLabel::Neutral => continue,
+        };
+        let language = language_of(comment);
+        let known = lexicon::store(language).current();
+        let words: BTreeSet<String> = keyphrases::content_words(&comment.content, language)
+            .into_iter()
+            .filter(|word| word.chars().count() >= 3 && !word.chars().any(|c| c.is_numeric()))
+            .filter(|word| !known.words.contains(word))
+            .collect();
+        for word in words {
+            counts.entry((language.code(), word)).or_default()[side] += 1;
+        }
+    }
+
+    let mut suggestions: Vec<TermSuggestion> = counts
+        .into_iter()
+        .filter_map(|((code, word), [negative, positive])| {
+            let (label, corrections, opposite) = if negative >= positive {
+                (SentimentLabel::Negative, negative, positive)
+            } else {
+                (SentimentLabel::Positive, positive, negative)
+            };
+            if corrections < min_count || corrections <= 2 * opposite {
+                return None;
+            }
+            Some(TermSuggestion { language: Language::from_code(code)?, word, label, corrections })
+        })
+        .collect();
+    suggestions.sort_by(|a, b| b.corrections.cmp(&a.corrections).then_with(|| a.word.cmp(&b.word)));
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comment(content: &str, auto_status: u8, human_status: Option<u8>) -> Comment {
+        Comment {
+            status: human_status.unwrap_or(auto_status),
+            auto_status,
+            human_status,
+            language: Some("en".to_string()),
+            ..Comment::sample(content)
+        }
+    }
+
+    #[test]
+    fn test_examples_use_the_human_label() {
+        let comments = [comment("Soggy chips again", 2, Some(1)), comment("Lovely terrace", 3, None)];
+        let examples = examples(&comments);
+        assert_eq!(examples.len(), 1);
+        assert_eq!(examples[0].label(), Some(SentimentLabel::Negative));
+        assert_eq!(examples[0].language.as_deref(), Some("en"));
+    }
+
+    #[test]
+    fn test_suggest_terms_from_corrections() {
+        let comments = [
+            comment("The chips were soggy", 2, Some(1)),
+            comment("Soggy fries and a soggy bun", 3, Some(1)),
+            comment("A soggy pizza base", 2, Some(1)),
+            comment("The terrace was soggy after the rain but we loved it", 2, Some(3)),
+            // confirmations of the analyzer teach the lexicon nothing
+            comment("Soggy and rude", 1, Some(1)),
+            // words the lexicon already knows are not suggested
+            comment("Rude staff, rude manager", 2, Some(1)),
+            comment("Rude again", 2, Some(1)),
+            comment("So rude", 2, Some(1)),
+        ];
+
+        let suggestions = suggest_terms(&comments, 3);
+        assert_eq!(
+            suggestions,
+            vec![TermSuggestion {
+                language: Language::English,
+                word: "soggy".to_string(),
+                label: SentimentLabel::Negative,
+                corrections: 3,
+            }]
+        );
+        assert!(suggest_terms(&comments, 4).is_empty());
+    }
+}
@@ -0,0 +1,259 @@
+//! Evaluation of analyzers against the labeled corpus in `data/eval/`.
+//!
+//! `dene-admin eval` prints the report; the test at the bottom fails the
+//! build when the keyword analyzer's accuracy drops below the baseline
+//! recorded in `data/eval/baseline.json`.
+
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+use super::bayes::TrainingExample;
+use super::language::{self, Language};
+use super::{analyze_sentences, build_analyzer, SentimentAnalyzer, SentimentLabel, SentimentResult};
+
+/// Labeled reviews, one JSON object per line (same format as training files).
+pub const CORPUS_PATH: &str = "data/eval/reviews.jsonl";
+/// Minimum accuracy per analyzer name.
+pub const BASELINE_PATH: &str = "data/eval/baseline.json";
+
+/// Confidence buckets for the calibration table: [0, 0.2), [0.2, 0.4), ...
+const CALIBRATION_BUCKETS: usize = 5;
+
+/// Metrics for one analyzer over a corpus. Labels are indexed `status - 1`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Report {
+    pub analyzer: String,
+    /// `confusion[gold][predicted]`.
+    pub confusion: [[u32; 3]; 3],
+    /// Examples whose language was detected as labeled.
+    pub language_hits: u32,
+    /// Sum of scores per gold label, for the mean score.
+    score_sums: [f64; 3],
+    /// Per confidence bucket: examples, correct predictions, confidence sum.
+    buckets: [(u32, u32, f64); CALIBRATION_BUCKETS],
+}
+
+impl Report {
+    pub fn total(&self) -> u32 {
+        self.confusion.iter().flatten().sum()
+    }
+
+    pub fn correct(&self) -> u32 {
+        (0..3).map(|i| self.confusion[i][i]).sum()
+    }
+
+    pub fn accuracy(&self) -> f64 {
+        ratio(self.correct(), self.total())
+    }
+
+    /// Of the examples predicted `label`, the share that were `label`.
+    pub fn precision(&self, label: SentimentLabel) -> f64 {
+        let i = label.status() as usize - 1;
+        ratio(self.confusion[i][i], (0..3).map(|g| self.confusion[g][i]).sum())
+    }
+
+    /// Of the examples labeled `label`, the share predicted `label`.
+    pub fn recall(&self, label: SentimentLabel) -> f64 {
+        let i = label.status() as usize - 1;
+        ratio(self.confusion[i][i], self.confusion[i].iter().sum())
+    }
+
+    pub fn f1(&self, label: SentimentLabel) -> f64 {
+        let (p, r) = (self.precision(label), self.recall(label));
+        if p + r == 0.0 { 0.0 } else { 2.0 * p * r / (p + r) }
+    }
+
+    pub fn macro_f1(&self) -> f64 {
+        SentimentLabel::ALL.iter().map(|l| self.f1(*l)).sum::<f64>() / 3.0
+    }
+
+    /// Mean 0–100 score of the examples labeled `label`; a calibrated
+    /// analyzer scores negatives below 50 and positives above.
+    pub fn mean_score(&self, label: SentimentLabel) -> Option<f64> {
+        let i = label.status() as usize - 1;
+        let count: u32 = self.confusion[i].iter().sum();
+        (count > 0).then(|| self.score_sums[i] / count as f64)
+    }
+
+    /// Expected calibration error: how far the stated confidence is from
+    /// the observed accuracy, averaged over buckets by size.
+    pub fn calibration_error(&self) -> f64 {
+        let total = self.total() as f64;
+        if total == 0.0 {
+            return 0.0;
+        }
+        self.buckets
+            .iter()
+            .filter(|(n, _, _)| *n > 0)
+            .map(|(n, correct, confidence)| {
+                let n = *n as f64;
+                (n / total) * (*confidence / n - *correct as f64 / n).abs()
+            })
+            .sum()
+    }
+
+    fn record(&mut self, gold: SentimentLabel, predicted: SentimentLabel, score: u8, confidence: f32) {
+        let (g, p) = (gold.status() as usize - 1, predicted.status() as usize - 1);
+        self.confusion[g][p] += 1;
+        self.score_sums[g] += score as f64;
+
+        let bucket = ((confidence.clamp(0.0, 1.0) * CALIBRATION_BUCKETS as f32) as usize).min(CALIBRATION_BUCKETS - 1);
+        let entry = &mut self.buckets[bucket];
+        entry.0 += 1;
+        entry.1 += (g == p) as u32;
+        entry.2 += confidence as f64;
+    }
+}
+
+fn ratio(part: u32, whole: u32) -> f64 {
+    if whole == 0 { 0.0 } else { part as f64 / whole as f64 }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Analyzer: {}", self.analyzer)?;
+        writeln!(f, "Accuracy: {:.1}% ({}/{})", self.accuracy() * 100.0, self.correct(), self.total())?;
+        writeln!(f, "Macro F1: {:.3}", self.macro_f1())?;
+        writeln!(f, "Language detection: {}/{}", self.language_hits, self.total())?;
+
+        writeln!(f, "\n{:<10} {:>9} {:>9} {:>9} {:>10}", "label", "precision", "recall", "f1", "mean score")?;
+        for label in SentimentLabel::ALL {
+            let mean = self.mean_score(label).map_or("-".to_string(), |m| format!("{:.1}", m));
+            writeln!(
+                f,
+                "{:<10} {:>9.3} {:>9.3} {:>9.3} {:>10}",
+                label.as_str(),
+                self.precision(label),
+                self.recall(label),
+                self.f1(label),
+                mean
+            )?;
+        }
+
+        writeln!(f, "\nConfusion (rows = gold, columns = predicted)")?;
+        writeln!(f, "{:<10} {:>9} {:>9} {:>9}", "", "negative", "neutral", "positive")?;
+        for label in SentimentLabel::ALL {
+            let row = self.confusion[label.status() as usize - 1];
+            writeln!(f, "{:<10} {:>9} {:>9} {:>9}", label.as_str(), row[0], row[1], row[2])?;
+        }
+
+        writeln!(f, "\nCalibration (expected error {:.3})", self.calibration_error())?;
+        writeln!(f, "{:<10} {:>9} {:>9} {:>9}", "confidence", "examples", "mean conf", "accuracy")?;
+        for (i, (n, correct, confidence)) in self.buckets.iter().enumerate() {
+            if *n == 0 {
+                continue;
+            }
+            let low = i as f64 / CALIBRATION_BUCKETS as f64;
+            let high = (i + 1) as f64 / CALIBRATION_BUCKETS as f64;
+            writeln!(
+                f,
+                "{:<10} {:>9} {:>9.3} {:>9.3}",
+                format!("{:.1}-{:.1}", low, high),
+                n,
+                confidence / *n as f64,
+                ratio(*correct, *n)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Run the analyzer named `analyzer` (as in `SENTIMENT_ANALYZER`) over the
+/// examples, sentence by sentence as reviews are scored when stored. Each
+/// example is scored by the analyzer for its labeled language, so detection
+/// mistakes are reported but do not affect the sentiment metrics.
+pub fn evaluate(analyzer: &str, examples: &[TrainingExample]) -> Result<Report, String> {
+    let mut analyzers: HashMap<Language, Box<dyn SentimentAnalyzer>> = HashMap::new();
+    let mut report = Report { analyzer: analyzer.to_string(), ..Report::default() };
+
+    for example in examples {
+        let language = example
+            .language
+            .as_deref()
+            .map(|code| Language::from_code(code).ok_or_else(|| format!("Unsupported language: {}", code)))
+            .transpose()?
+            .or_else(|| language::detect(&example.text))
+            .ok_or_else(|| format!("Cannot detect the language of {:?}", example.text))?;
+        let gold = example.label().ok_or_else(|| format!("Unknown label {:?}", example.label))?;
+
+        if language::detect(&example.text) == Some(language) {
+            report.language_hits += 1;
+        }
+
+        let model = match analyzers.entry(language) {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => e.insert(build_analyzer(analyzer, language)?),
+        };
+        let result = SentimentResult::combine(&analyze_sentences(&example.text, |s| model.analyze(s)));
+        report.record(gold, result.label, result.score, result.confidence);
+    }
+
+    Ok(report)
+}
+
+/// Read the baseline accuracies, keyed by analyzer name.
+pub fn load_baseline(path: &Path) -> Result<BTreeMap<String, f64>, String> {
+    let source = std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    serde_json::from_str(&source).map_err(|e| format!("Invalid baseline {}: {}", path.display(), e))
+}
+
+/// Fail if the report's accuracy is below the baseline for its analyzer.
+/// Analyzers without a baseline pass.
+pub fn check_baseline(report: &Report, baseline: &BTreeMap<String, f64>) -> Result<(), String> {
+    match baseline.get(&report.analyzer) {
+        Some(min) if report.accuracy() + f64::EPSILON < *min => Err(format!(
+            "{} accuracy {:.3} is below the baseline {:.3}",
+            report.analyzer,
+            report.accuracy(),
+            min
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::bayes::read_examples;
+
+    fn repo_path(path: &str) -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join(path)
+    }
+
+    #[test]
+    fn test_metrics() {
+        let mut report = Report::default();
+        let (neg, neu, pos) = (SentimentLabel::Negative, SentimentLabel::Neutral, SentimentLabel::Positive);
+        report.record(neg, neg, 20, 0.9);
+        report.record(neg, neu, 50, 0.1);
+        report.record(pos, pos, 80, 0.9);
+        report.record(neu, pos, 60, 0.5);
+
+        assert_eq!(report.total(), 4);
+        assert_eq!(report.accuracy(), 0.5);
+        assert_eq!(report.precision(pos), 0.5);
+        assert_eq!(report.recall(neg), 0.5);
+        assert_eq!(report.f1(neu), 0.0);
+        assert_eq!(report.mean_score(neg), Some(35.0));
+        assert!((report.calibration_error() - 0.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_check_baseline() {
+        let mut report = Report { analyzer: "keyword".to_string(), ..Report::default() };
+        report.record(SentimentLabel::Positive, SentimentLabel::Negative, 0, 1.0);
+        let baseline = BTreeMap::from([("keyword".to_string(), 0.5)]);
+        assert!(check_baseline(&report, &baseline).is_err());
+        report.analyzer = "naive_bayes".to_string();
+        assert!(check_baseline(&report, &baseline).is_ok());
+    }
+
+    #[test]
+    fn test_keyword_analyzer_meets_baseline() {
+        let examples = read_examples(&repo_path(CORPUS_PATH)).unwrap();
+        let report = evaluate("keyword", &examples).unwrap();
+        let baseline = load_baseline(&repo_path(BASELINE_PATH)).unwrap();
+        check_baseline(&report, &baseline).unwrap_or_else(|e| panic!("{}\n\n{}", e, report));
+    }
+}
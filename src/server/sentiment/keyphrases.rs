@@ -0,0 +1,144 @@
+//! Keyphrase extraction for trending topics ("cold soup", "slow service").
+//!
+//! A keyphrase is a run of up to three content words: stopwords and clause
+//! punctuation end a run, so "the soup was cold, the bread stale" yields
+//! "soup", "cold" and "bread stale". Words are lowercased and plural
+//! endings removed, so "cold soups" and "Cold soup" count as one phrase.
+//! The stopword lists live in `data/keyphrases/stopwords.toml` and are
+//! compiled into the binary.
+
+use std::collections::{HashMap, HashSet};
+use once_cell::sync::Lazy;
+use super::language::Language;
+use super::tokenizer::{tokenize, Token};
+
+static STOPWORDS: Lazy<HashMap<String, HashSet<String>>> = Lazy::new(|| {
+    toml::from_str(include_str!("../../../data/keyphrases/stopwords.toml")).expect("built-in stopword lists are valid")
+});
+
+/// Longest keyphrase, in words. Longer runs yield every phrase of this
+/// length they contain.
+const MAX_WORDS: usize = 3;
+
+/// Shortest word, in characters, that can be part of a keyphrase.
+const MIN_WORD_CHARS: usize = 2;
+
+fn is_stopword(word: &str, language: Language) -> bool {
+    STOPWORDS.get(language.code()).is_some_and(|words| words.contains(word))
+}
+
+/// Dictionary form of a lowercased word: plural endings are removed where
+/// that can be done without a dictionary. German plurals are too irregular
+/// and are left as written.
+pub fn lemma(word: &str, language: Language) -> String {
+    let chars = word.chars().count();
+    let strip = |suffix: &str, replacement: &str| format!("{}{}", &word[..word.len() - suffix.len()], replacement);
+    match language {
+        Language::English => {
+            if chars > 4 && word.ends_with("ies") {
+                strip("ies", "y")
+            } else if ["sses", "ches", "shes", "xes"].iter().any(|s| word.ends_with(s)) {
+                strip("es", "")
+            } else if chars > 3 && word.ends_with('s') && !["ss", "us", "is"].iter().any(|s| word.ends_with(s)) {
+                strip("s", "")
+            } else {
+                word.to_string()
+            }
+        }
+        Language::Turkish => match ["lar", "ler"].into_iter().find(|s| word.ends_with(s)) {
+            Some(suffix) if chars >= suffix.len() + 3 => strip(suffix, ""),
+            _ => word.to_string(),
+        },
+        Language::French => {
+            if word.ends_with("eaux") {
+                strip("x", "")
+            } else if chars > 3 && ["es", "és", "ts", "rs", "ls", "ns", "ds"].iter().any(|s| word.ends_with(s)) {
+                strip("s", "")
+            } else {
+                word.to_string()
+            }
+        }
+        Language::German => word.to_string(),
+    }
+}
+
+/// A word that can be part of a keyphrase: not a stopword, not a number.
+fn is_content_word(token: &Token, language: Language) -> bool {
+    token.text.chars().count() >= MIN_WORD_CHARS
+        && token.text.chars().any(char::is_alphabetic)
+        && !is_stopword(&token.text, language)
+}
+
+/// Lemmas of the content words of `text`, in text order.
+pub fn content_words(text: &str, language: Language) -> Vec<String> {
+    tokenize(text)
+        .iter()
+        .filter(|token| is_content_word(token, language))
+        .map(|token| lemma(&token.text, language))
+        .collect()
+}
+
+/// Content words plus multi-word keyphrases, for comparing texts: "cold
+/// soup" weighs more than "cold" and "soup" apart.
+pub fn terms(text: &str, language: Language) -> Vec<String> {
+    let mut terms = content_words(text, language);
+    terms.extend(extract(text, language).into_iter().filter(|p| p.contains(' ')));
+    terms
+}
+
+/// Keyphrases of `text`, each once, in order of first occurrence.
+pub fn extract(text: &str, language: Language) -> Vec<String> {
+    let mut runs: Vec<Vec<String>> = Vec::new();
+    let mut run: Vec<String> = Vec::new();
+    for token in tokenize(text) {
+        if token.boundary_before || !is_content_word(&token, language) {
+            runs.push(std::mem::take(&mut run));
+        }
+        if is_content_word(&token, language) {
+            run.push(lemma(&token.text, language));
+        }
+    }
+    runs.push(run);
+
+    let mut seen = HashSet::new();
+    runs.iter()
+        .filter(|run| !run.is_empty())
+        .flat_map(|run| run.windows(run.len().min(MAX_WORDS)).map(|words| words.join(" ")))
+        .filter(|phrase| seen.insert(phrase.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lemma() {
+        assert_eq!(lemma("soups", Language::English), "soup");
+        assert_eq!(lemma("dishes", Language::English), "dish");
+        assert_eq!(lemma("berries", Language::English), "berry");
+        assert_eq!(lemma("glass", Language::English), "glass");
+        assert_eq!(lemma("yemekler", Language::Turkish), "yemek");
+        assert_eq!(lemma("gâteaux", Language::French), "gâteau");
+        assert_eq!(lemma("serveurs", Language::French), "serveur");
+        assert_eq!(lemma("repas", Language::French), "repas");
+    }
+
+    #[test]
+    fn test_extract_runs_between_stopwords() {
+        assert_eq!(extract("The soup was cold, the bread stale.", Language::English), vec!["soup", "cold", "bread stale"]);
+        assert_eq!(
+            extract("Very cold soups again! Cold soup and 2 slow waiters", Language::English),
+            vec!["cold soup", "slow waiter"]
+        );
+        assert_eq!(extract("La soupe était froide", Language::French), vec!["soupe", "froide"]);
+    }
+
+    #[test]
+    fn test_extract_splits_long_runs() {
+        assert_eq!(
+            extract("grilled sea bass special", Language::English),
+            vec!["grilled sea bass", "sea bass special"]
+        );
+    }
+}
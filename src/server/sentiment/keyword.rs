@@ -0,0 +1,456 @@
+use std::sync::Arc;
+use crate::model::Aspect;
+use super::language::Language;
+use super::lexicon::{self, Lexicon, Role};
+use super::signals::find_symbols;
+use super::tokenizer::{collapse_runs, match_phrases, tokenize, Token};
+use super::{Evidence, SentimentAnalyzer, SentimentResult};
+
+/// Negated hits flip and lose some strength: "not bad" is milder than "good".
+const NEGATION_FACTOR: f64 = 0.75;
+/// How many words a negator reaches forward (clause punctuation stops it earlier).
+pub(super) const NEGATION_SCOPE: usize = 3;
+/// How many words a post-negator ("iyi değil") reaches back.
+const POST_NEGATION_SCOPE: usize = 2;
+/// How many words an intensifier or dampener reaches forward.
+const MODIFIER_SCOPE: usize = 2;
+/// Weight of hits in clauses before the last clause that has any hits.
+const EARLIER_CLAUSE_WEIGHT: f64 = 0.5;
+/// Stretched ("goood") or capitalised ("AWFUL") words count this much more,
+/// each.
+const EMPHASIS_BOOST: f64 = 1.5;
+/// Extra weight per exclamation mark closing a clause.
+const EXCLAMATION_BOOST: f64 = 0.2;
+/// Exclamation marks beyond this add nothing.
+const MAX_EXCLAMATIONS: usize = 3;
+
+pub(super) fn is_negated_contraction(word: &str) -> bool {
+    word.ends_with("n't") || word.ends_with("n\u{2019}t")
+}
+
+/// A weighted lexicon hit, the clause it occurred in and its token range.
+struct Hit<'a> {
+    term: &'a str,
+    weight: f64,
+    aspect: Option<Aspect>,
+    clause: usize,
+    /// Stretch of text between punctuation or contrast words; aspect cues
+    /// only apply to hits in the same segment.
+    segment: usize,
+    start: usize,
+    end: usize,
+}
+
+/// Emphasis carried by the tokens of a hit: stretched letters and capitals.
+fn emphasis(tokens: &[Token]) -> f64 {
+    let boost = |on: bool| if on { EMPHASIS_BOOST } else { 1.0 };
+    boost(tokens.iter().any(|t| t.elongated)) * boost(tokens.iter().any(|t| t.shouted))
+}
+
+/// Stretched words are shortened to two letters per run by the tokenizer;
+/// when that is not a lexicon word, try one letter ("sooo" → "so",
+/// "amaaazing" → "amazing").
+fn resolve_elongations(lexicon: &Lexicon, tokens: &mut [Token]) {
+    for token in tokens.iter_mut().filter(|t| t.elongated && !lexicon.words.contains(&t.text)) {
+        let single = collapse_runs(&token.text, 1);
+        if lexicon.words.contains(&single) {
+            token.text = single;
+        }
+    }
+}
+
+/// Attribute hits without an aspect of their own to the nearest aspect cue
+/// in the same segment, preferring the earlier cue on a tie ("the waiter
+/// was great" names the subject first).
+fn attribute_aspects(hits: &mut [Hit], cues: &[(usize, usize, Aspect)]) {
+    for hit in hits.iter_mut().filter(|h| h.aspect.is_none()) {
+        hit.aspect = cues
+            .iter()
+            .filter(|(segment, _, _)| *segment == hit.segment)
+            .min_by_key(|(_, pos, _)| (pos.abs_diff(hit.start), *pos > hit.start))
+            .map(|(_, _, aspect)| *aspect);
+    }
+}
+
+/// Walk the tokens left to right, applying negation and modifier scopes to
+/// each lexicon hit, numbering clauses split by contrast words and tagging
+/// hits with the aspect they talk about.
+fn collect_hits<'a>(lexicon: &'a Lexicon, tokens: &[Token]) -> Vec<Hit<'a>> {
+    let mut matches = match_phrases(tokens, &lexicon.phrases, lexicon.stems).into_iter().peekable();
+    let mut hits: Vec<Hit> = Vec::new();
+    let mut clause = 0;
+    let mut negated_until = 0;
+    let mut modifier: Option<(f64, usize)> = None;
+    // Index of the last hit a post-negator may still flip.
+    let mut post_target: Option<usize> = None;
+    let mut segment = 0;
+    let mut cues = Vec::new();
+    // Exclamation marks closing each segment.
+    let mut exclamations: Vec<usize> = vec![0];
+
+    let mut i = 0;
+    while i < tokens.len() {
+        if tokens[i].boundary_before {
+            negated_until = 0;
+            modifier = None;
+            post_target = None;
+            segment += 1;
+        }
+        exclamations.resize(segment + 1, 0);
+        exclamations[segment] = exclamations[segment].max(tokens[i].exclamations);
+
+        let (term, role, category, len) = match matches.peek() {
+            Some(m) if m.start == i => {
+                let (term, role, category) = &lexicon.roles[m.phrase];
+                let len = m.len;
+                matches.next();
+                (term.as_str(), *role, *category, len)
+            }
+            _ if is_negated_contraction(&tokens[i].text) => ("n't", Role::Negator, None, 1),
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+
+        match role {
+            Role::Negator => negated_until = i + len + NEGATION_SCOPE,
+            Role::PostNegator => {
+                if let Some(hit) = post_target.take().map(|h| &mut hits[h]) {
+                    if i <= hit.end + POST_NEGATION_SCOPE {
+                        hit.weight *= -NEGATION_FACTOR;
+                    }
+                }
+            }
+            Role::Modifier(m) => {
+                let stacked = modifier.filter(|(_, until)| i < *until).map_or(1.0, |(prev, _)| prev);
+                // "sooo" intensifies more than "so"; emphasis never weakens a dampener
+                let m = if m > 1.0 { m * emphasis(&tokens[i..i + len]) } else { m };
+                modifier = Some((stacked * m, i + len + MODIFIER_SCOPE));
+            }
+            Role::Contrast => {
+                clause += 1;
+                segment += 1;
+                exclamations.resize(segment + 1, 0);
+                negated_until = 0;
+                modifier = None;
+                post_target = None;
+            }
+            Role::Aspect(aspect) => cues.push((segment, i, aspect)),
+            Role::Sentiment(base) => {
+                let mut weight = base * emphasis(&tokens[i..i + len]);
+                if let Some((m, until)) = modifier.take() {
+                    if i < until {
+                        weight *= m;
+                    }
+                }
+                if i < negated_until {
+                    weight *= -NEGATION_FACTOR;
+                }
+                post_target = Some(hits.len());
+                hits.push(Hit { term, weight, aspect: category, clause, segment, start: i, end: i + len });
+            }
+        }
+
+        for token in &tokens[i + 1..i + len] {
+            exclamations[segment] = exclamations[segment].max(token.exclamations);
+        }
+        i += len;
+    }
+
+    for hit in &mut hits {
+        hit.weight *= 1.0 + EXCLAMATION_BOOST * exclamations[hit.segment].min(MAX_EXCLAMATIONS) as f64;
+    }
+    attribute_aspects(&mut hits, &cues);
+    hits
+}
+
+/// Rule-based analyzer over a [`Lexicon`].
+///
+/// Each lexicon hit contributes its weight, scaled by any intensifier or
+/// dampener before it and flipped when it falls inside a negation scope.
+/// When a review has several clauses joined by "but"-style contrasts, the
+/// last clause with hits counts in full and earlier ones are discounted.
+/// Emoji and emoticons add their table weight. The label follows the sign
+/// of the total; the score reflects its density over the review
+/// (50 = neutral).
+#[derive(Debug, Clone)]
+pub struct KeywordAnalyzer {
+    language: Language,
+    /// Fixed lexicon; `None` follows the shared, hot-reloaded one.
+    lexicon: Option<Arc<Lexicon>>,
+}
+
+impl KeywordAnalyzer {
+    /// An analyzer that always uses the current shared lexicon of `language`.
+    pub fn new(language: Language) -> Self {
+        KeywordAnalyzer { language, lexicon: None }
+    }
+
+    /// An analyzer pinned to a specific lexicon.
+    pub fn with_lexicon(lexicon: Arc<Lexicon>) -> Self {
+        KeywordAnalyzer { language: lexicon.language, lexicon: Some(lexicon) }
+    }
+
+    fn lexicon(&self) -> Arc<Lexicon> {
+        self.lexicon.clone().unwrap_or_else(|| lexicon::store(self.language).current())
+    }
+}
+
+impl SentimentAnalyzer for KeywordAnalyzer {
+    fn name(&self) -> &'static str {
+        "keyword"
+    }
+
+    /// `keyword/<language>/<lexicon version>`; follows lexicon reloads.
+    fn version(&self) -> String {
+        format!("keyword/{}/{}", self.language.code(), self.lexicon().version)
+    }
+
+    fn analyze(&self, text: &str) -> SentimentResult {
+        let lexicon = self.lexicon();
+        let mut tokens = tokenize(text);
+        resolve_elongations(&lexicon, &mut tokens);
+        let symbols = find_symbols(text);
+        let word_count = tokens.len() + symbols.len();
+
+        let hits = collect_hits(&lexicon, &tokens);
+        let last_clause = hits.iter().map(|h| h.clause).max().unwrap_or(0);
+
+        let mut evidence: Vec<Evidence> = hits
+            .into_iter()
+            .map(|h| {
+                let clause_weight = if h.clause == last_clause { 1.0 } else { EARLIER_CLAUSE_WEIGHT };
+                Evidence {
+                    term: h.term.to_string(),
+                    weight: h.weight * clause_weight,
+                    aspect: h.aspect,
+                    start: tokens[h.start].start,
+                    end: tokens[h.end - 1].end,
+                }
+            })
+            .collect();
+
+        // Emoji and emoticons count as they are, outside clause and negation scopes.
+        evidence.extend(symbols.into_iter().map(Evidence::from));
+        evidence.sort_by_key(|e| e.start);
+
+        SentimentResult::from_evidence(evidence, word_count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::SentimentLabel;
+
+    fn english() -> KeywordAnalyzer {
+        KeywordAnalyzer::new(Language::English)
+    }
+
+    #[test]
+    fn test_evidence_spans_cover_matched_text() {
+        let text = "Sadly I got FOOD POISONING, not good";
+        let spans: Vec<&str> = english().analyze(text).evidence.iter().map(|e| &text[e.start..e.end]).collect();
+        assert_eq!(spans, ["FOOD POISONING", "good"]);
+    }
+
+    #[test]
+    fn test_evidence_lists_matched_terms() {
+        let result = english().analyze("Friendly staff but the soup was cold");
+        let terms: Vec<&str> = result.evidence.iter().map(|e| e.term.as_str()).collect();
+        assert!(terms.contains(&"friendly"));
+        assert!(terms.contains(&"cold"));
+    }
+
+    #[test]
+    fn test_label_and_score_agree() {
+        let result = english().analyze("terrible, rude and overpriced");
+        assert_eq!(result.label, SentimentLabel::Negative);
+        assert!(result.score < 50);
+        assert_eq!(result.confidence, 1.0);
+    }
+
+    fn terms(text: &str) -> Vec<String> {
+        english().analyze(text).evidence.into_iter().map(|e| e.term).collect()
+    }
+
+    #[test]
+    fn test_no_substring_false_positives() {
+        assert!(terms("A butterfly landed on the table").is_empty());
+        assert!(terms("Napkins are kept in the drawer").is_empty());
+        assert!(terms("The child was scolded by his mother").is_empty());
+        assert!(terms("We said goodbye to the chef").is_empty());
+    }
+
+    #[test]
+    fn test_whole_words_still_match() {
+        assert_eq!(terms("There was a fly in my soup"), ["fly"]);
+        assert_eq!(terms("The fish was raw."), ["raw"]);
+        assert_eq!(terms("Cold, cold coffee"), ["cold", "cold"]);
+        assert_eq!(terms("Good!"), ["good"]);
+    }
+
+    #[test]
+    fn test_multi_word_phrases() {
+        assert_eq!(terms("Never again. Food poisoning after the oysters"), ["never again", "food poisoning"]);
+        assert_eq!(terms("Top-notch service"), ["top-notch"]);
+        assert!(terms("I will never go back again").is_empty());
+    }
+
+    fn label(text: &str) -> SentimentLabel {
+        english().analyze(text).label
+    }
+
+    #[test]
+    fn test_negation_flips_polarity() {
+        assert_eq!(label("Not bad at all"), SentimentLabel::Positive);
+        assert_eq!(label("The dessert was never disappointing"), SentimentLabel::Positive);
+        assert_eq!(label("The steak was not good"), SentimentLabel::Negative);
+        assert_eq!(label("It wasn't fresh"), SentimentLabel::Negative);
+        assert_eq!(label("It wasn\u{2019}t fresh"), SentimentLabel::Negative);
+    }
+
+    #[test]
+    fn test_negation_scope_ends_at_punctuation() {
+        // "not" must not reach across the comma to "delicious"
+        assert_eq!(label("Not cheap, but delicious"), SentimentLabel::Positive);
+        assert_eq!(label("No, it was delicious"), SentimentLabel::Positive);
+        // nor more than a few words ahead
+        assert_eq!(label("Not what I expected from the menu description, delicious"), SentimentLabel::Positive);
+    }
+
+    #[test]
+    fn test_intensifiers_and_dampeners_scale_hits() {
+        let weight = |text: &str| english().analyze(text).evidence[0].weight;
+        assert_eq!(weight("bad"), -1.0);
+        assert_eq!(weight("very bad"), -1.5);
+        assert_eq!(weight("extremely bad"), -2.0);
+        assert_eq!(weight("slightly bad"), -0.5);
+        assert_eq!(weight("a bit bad"), -0.5);
+        assert_eq!(weight("not very good"), -1.125);
+        // the boost only applies to the next hit
+        assert_eq!(english().analyze("very good and fresh").evidence[1].weight, 1.0);
+    }
+
+    #[test]
+    fn test_last_clause_dominates() {
+        assert_eq!(label("Friendly staff but the soup was cold"), SentimentLabel::Negative);
+        assert_eq!(label("The soup was cold, however the dessert was lovely"), SentimentLabel::Positive);
+        // a trailing contrast with no hits does not discount what came before
+        let result = english().analyze("Lovely evening though");
+        assert_eq!(result.evidence[0].weight, 1.0);
+    }
+
+    #[test]
+    fn test_intensity_moves_score() {
+        let mild = english().analyze("the soup was slightly cold").score;
+        let strong = english().analyze("the soup was extremely cold").score;
+        assert!(strong < mild);
+        assert!(mild < 50);
+    }
+
+    #[test]
+    fn test_pinned_lexicon() {
+        let lexicon = Lexicon::parse("[[terms]]\nword = \"meh\"\npolarity = \"negative\"\nweight = 2.0", false).unwrap();
+        let analyzer = KeywordAnalyzer::with_lexicon(Arc::new(lexicon));
+        let result = analyzer.analyze("Meh. Good bread though");
+        assert_eq!(result.label, SentimentLabel::Negative);
+        assert_eq!(result.evidence, vec![Evidence {
+            term: "meh".to_string(),
+            weight: -2.0,
+            aspect: None,
+            start: 0,
+            end: 3,
+        }]);
+    }
+
+    fn label_in(language: Language, text: &str) -> SentimentLabel {
+        KeywordAnalyzer::new(language).analyze(text).label
+    }
+
+    #[test]
+    fn test_turkish() {
+        assert_eq!(label_in(Language::Turkish, "Yemekler çok lezzetliydi"), SentimentLabel::Positive);
+        assert_eq!(label_in(Language::Turkish, "Yemek hiç iyi değildi"), SentimentLabel::Negative);
+        assert_eq!(label_in(Language::Turkish, "Fiyatlar uygunsuz"), SentimentLabel::Negative);
+        assert_eq!(label_in(Language::Turkish, "Garson kabaydı ama yemekler harikaydı"), SentimentLabel::Positive);
+    }
+
+    #[test]
+    fn test_french() {
+        assert_eq!(label_in(Language::French, "Ce n'était pas bon"), SentimentLabel::Negative);
+        assert_eq!(label_in(Language::French, "L'accueil était très chaleureux"), SentimentLabel::Positive);
+        assert_eq!(label_in(Language::French, "Plus jamais, c'est hors de prix"), SentimentLabel::Negative);
+    }
+
+    #[test]
+    fn test_german() {
+        assert_eq!(label_in(Language::German, "Das Essen war nicht gut"), SentimentLabel::Negative);
+        assert_eq!(label_in(Language::German, "Sehr lecker, aber der Kellner war unfreundlich"), SentimentLabel::Negative);
+    }
+
+    fn aspects(text: &str) -> Vec<(Aspect, SentimentLabel)> {
+        english().analyze(text).aspects().into_iter().map(|a| (a.aspect, SentimentLabel::from_status(a.status))).collect()
+    }
+
+    #[test]
+    fn test_emoji_and_emoticons() {
+        let result = english().analyze("sooo goood 😍😍");
+        assert_eq!(result.label, SentimentLabel::Positive);
+        assert_eq!(result.evidence.iter().map(|e| e.term.as_str()).collect::<Vec<_>>(), ["good", "😍", "😍"]);
+
+        let result = english().analyze("meh 🙄 :(");
+        assert_eq!(result.label, SentimentLabel::Negative);
+        assert!(result.score < 20);
+    }
+
+    #[test]
+    fn test_emphasis_boosts_weight() {
+        let weight = |text: &str| english().analyze(text).evidence[0].weight;
+        assert_eq!(weight("good food"), 1.0);
+        assert_eq!(weight("goood food"), 1.5);
+        assert_eq!(weight("GOOD food"), 1.5);
+        assert_eq!(weight("GOOOOD food"), 2.25);
+        assert_eq!(weight("amaaazing food"), 1.5);
+        assert!((weight("good food!!") - 1.4).abs() < 1e-9);
+        assert!((weight("good food!!!!!!") - 1.6).abs() < 1e-9);
+        // the exclamation closes the first clause only
+        assert_eq!(english().analyze("good! cold soup").evidence[1].weight, -1.0);
+        // stretched intensifiers boost more
+        assert!(weight("sooo good") > weight("so good"));
+    }
+
+    #[test]
+    fn test_emphasis_raises_score() {
+        let score = |text: &str| english().analyze(text).score;
+        assert!(score("The food was GOOD!!!") > score("The food was good"));
+        assert!(score("The food was terrrrible") < score("The food was terrible"));
+    }
+
+    #[test]
+    fn test_aspects_from_term_categories() {
+        assert_eq!(aspects("Overpriced and unhygienic"), [
+            (Aspect::Price, SentimentLabel::Negative),
+            (Aspect::Hygiene, SentimentLabel::Negative),
+        ]);
+    }
+
+    #[test]
+    fn test_aspects_from_nearest_cue() {
+        assert_eq!(aspects("The waiter was terrible but the food was great"), [
+            (Aspect::Food, SentimentLabel::Positive),
+            (Aspect::Service, SentimentLabel::Negative),
+        ]);
+        // cues do not reach across punctuation
+        assert_eq!(aspects("Great. The music played all night"), []);
+        assert_eq!(aspects("Great music, awful"), [(Aspect::Ambience, SentimentLabel::Positive)]);
+    }
+
+    #[test]
+    fn test_aspects_in_other_languages() {
+        let result = KeywordAnalyzer::new(Language::German).analyze("Der Kellner war sehr gut, das Essen schlecht");
+        let found: Vec<_> = result.aspects().into_iter().map(|a| (a.aspect, a.status)).collect();
+        assert_eq!(found, [(Aspect::Food, 1), (Aspect::Service, 3)]);
+    }
+}
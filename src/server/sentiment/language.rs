@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+
+use super::lexicon::{self, Lexicon};
+use super::tokenizer::{match_phrases, tokenize};
+
+/// Languages with a lexicon and analyzer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Language {
+    English,
+    Turkish,
+    French,
+    German,
+}
+
+impl Language {
+    pub const ALL: [Language; 4] = [Language::English, Language::Turkish, Language::French, Language::German];
+
+    /// ISO 639-1 code, as stored on comments.
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Turkish => "tr",
+            Language::French => "fr",
+            Language::German => "de",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Language::ALL.into_iter().find(|l| l.code().eq_ignore_ascii_case(code.trim()))
+    }
+
+    /// Letters that hint at the language even in very short reviews.
+    /// Letters shared between languages (ö, ü, ç) count half for each.
+    fn letter_hints(self) -> &'static [(char, f64)] {
+        match self {
+            Language::English => &[],
+            Language::Turkish => &[('ı', 1.0), ('ğ', 1.0), ('ş', 1.0), ('ö', 0.5), ('ü', 0.5), ('ç', 0.5)],
+            Language::French => &[
+                ('é', 1.0), ('è', 1.0), ('ê', 1.0), ('à', 1.0), ('â', 1.0), ('ù', 1.0),
+                ('î', 1.0), ('ô', 1.0), ('û', 1.0), ('œ', 1.0), ('ë', 1.0), ('ç', 0.5),
+            ],
+            Language::German => &[('ä', 1.0), ('ß', 1.0), ('ö', 0.5), ('ü', 0.5)],
+        }
+    }
+}
+
+/// Evidence needed before a language is trusted; below it the review is
+/// left unanalyzed rather than scored with the wrong lexicon.
+const MIN_LANGUAGE_SCORE: f64 = 1.0;
+
+/// Guess the language of a review from the shared lexicons.
+pub fn detect(text: &str) -> Option<Language> {
+    let lexicons: Vec<_> = Language::ALL.into_iter().map(|l| lexicon::store(l).current()).collect();
+    detect_with(text, lexicons.iter().map(|l| l.as_ref()))
+}
+
+/// Guess the language of a review. Each lexicon votes with its stopwords,
+/// its sentiment and modifier terms, and characteristic letters; the
+/// language with the most votes wins if it clears `MIN_LANGUAGE_SCORE`.
+pub fn detect_with<'a>(text: &str, lexicons: impl IntoIterator<Item = &'a Lexicon>) -> Option<Language> {
+    let tokens = tokenize(text);
+    let lower = text.to_lowercase();
+
+    let mut scores: HashMap<Language, f64> = HashMap::new();
+    for lexicon in lexicons {
+        let stopwords = tokens.iter().filter(|t| lexicon.stopwords.contains(&t.text)).count() as f64;
+        let terms = match_phrases(&tokens, &lexicon.phrases, lexicon.stems).len() as f64;
+        let letters: f64 = lexicon
+            .language
+            .letter_hints()
+            .iter()
+            .map(|(c, w)| if lower.contains(*c) { *w } else { 0.0 })
+            .sum();
+        *scores.entry(lexicon.language).or_default() += stopwords + terms + letters;
+    }
+
+    // Ties resolve in `Language::ALL` order, so English wins an exact tie.
+    let mut best: Option<(Language, f64)> = None;
+    for language in Language::ALL {
+        let score = scores.get(&language).copied().unwrap_or(0.0);
+        if best.is_none_or(|(_, b)| score > b) {
+            best = Some((language, score));
+        }
+    }
+
+    best.filter(|(_, score)| *score >= MIN_LANGUAGE_SCORE).map(|(language, _)| language)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect_builtin(text: &str) -> Option<Language> {
+        let lexicons: Vec<Lexicon> = Language::ALL.into_iter().map(Lexicon::builtin).collect();
+        detect_with(text, &lexicons)
+    }
+
+    #[test]
+    fn test_detects_supported_languages() {
+        assert_eq!(detect_builtin("The soup was cold and the waiter was rude"), Some(Language::English));
+        assert_eq!(detect_builtin("Yemekler çok lezzetliydi ama garson biraz ilgisizdi"), Some(Language::Turkish));
+        assert_eq!(detect_builtin("Le plat était froid et le serveur très désagréable"), Some(Language::French));
+        assert_eq!(detect_builtin("Das Essen war kalt und der Kellner sehr unfreundlich"), Some(Language::German));
+    }
+
+    #[test]
+    fn test_detects_short_reviews_from_lexicon_terms() {
+        assert_eq!(detect_builtin("Amazing!"), Some(Language::English));
+        assert_eq!(detect_builtin("Harika!"), Some(Language::Turkish));
+        assert_eq!(detect_builtin("Délicieux"), Some(Language::French));
+    }
+
+    #[test]
+    fn test_unknown_language() {
+        assert_eq!(detect_builtin("Cibo ottimo e servizio veloce"), None);
+        assert_eq!(detect_builtin("12345 ..."), None);
+        assert_eq!(detect_builtin(""), None);
+    }
+
+    #[test]
+    fn test_language_codes_round_trip() {
+        for language in Language::ALL {
+            assert_eq!(Language::from_code(language.code()), Some(language));
+        }
+        assert_eq!(Language::from_code("it"), None);
+    }
+}
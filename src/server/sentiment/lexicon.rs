@@ -0,0 +1,473 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+use once_cell::sync::OnceCell;
+use serde::Deserialize;
+
+use crate::model::Aspect;
+use super::language::Language;
+use super::tokenizer::phrase_words;
+
+/// Lexicon compiled into the binary, used when `SENTIMENT_LEXICON_DIR` has
+/// no file for the language.
+fn builtin_source(language: Language) -> &'static str {
+    match language {
+        Language::English => include_str!("../../../data/lexicon/en.toml"),
+        Language::Turkish => include_str!("../../../data/lexicon/tr.toml"),
+        Language::French => include_str!("../../../data/lexicon/fr.toml"),
+        Language::German => include_str!("../../../data/lexicon/de.toml"),
+    }
+}
+
+/// Upper bound for term weights and modifier multipliers, to catch typos
+/// like `weight = 15` before they swamp every other term.
+const MAX_WEIGHT: f64 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Polarity {
+    Positive,
+    Negative,
+}
+
+/// One `[[terms]]` entry of a lexicon file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LexiconEntry {
+    pub word: String,
+    pub polarity: Polarity,
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+    /// Aspect the term is about on its own ("overpriced" is about price).
+    #[serde(default)]
+    pub category: Option<Aspect>,
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+/// Negation, intensity and contrast words of a lexicon file.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Modifiers {
+    #[serde(default)]
+    pub negators: Vec<String>,
+    /// Negators that follow the word they negate (Turkish "iyi değil").
+    #[serde(default)]
+    pub post_negators: Vec<String>,
+    #[serde(default)]
+    pub contrasts: Vec<String>,
+    #[serde(default)]
+    pub intensifiers: BTreeMap<String, f64>,
+    #[serde(default)]
+    pub dampeners: BTreeMap<String, f64>,
+}
+
+/// On-disk lexicon layout, shared by the TOML and JSON formats.
+#[derive(Debug, Clone, Deserialize)]
+struct LexiconFile {
+    #[serde(default = "default_version")]
+    version: String,
+    #[serde(default = "default_language")]
+    language: String,
+    /// Let single words also match longer words starting with them, for
+    /// languages that inflect with suffixes.
+    #[serde(default)]
+    stems: bool,
+    /// Common function words, used for language detection.
+    #[serde(default)]
+    stopwords: Vec<String>,
+    #[serde(default)]
+    modifiers: Modifiers,
+    /// Words naming an aspect ("waiter" → service). Sentiment terms without
+    /// a category of their own are attributed to the nearest one.
+    #[serde(default)]
+    aspects: BTreeMap<Aspect, Vec<String>>,
+    terms: Vec<LexiconEntry>,
+}
+
+fn default_version() -> String {
+    "unversioned".to_string()
+}
+
+fn default_language() -> String {
+    "en".to_string()
+}
+
+/// What a matched phrase does to the scoring.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Role {
+    /// Signed weight: negative terms pull the score down.
+    Sentiment(f64),
+    Negator,
+    PostNegator,
+    /// Multiplier for the next sentiment hit.
+    Modifier(f64),
+    Contrast,
+    /// Names an aspect without carrying sentiment.
+    Aspect(Aspect),
+}
+
+/// A validated lexicon with every term tokenized for phrase matching.
+#[derive(Debug, Clone)]
+pub struct Lexicon {
+    pub version: String,
+    pub language: Language,
+    pub stems: bool,
+    pub stopwords: HashSet<String>,
+    pub entries: Vec<LexiconEntry>,
+    /// Token sequences, parallel to `roles`, for `match_phrases`.
+    pub phrases: Vec<Vec<String>>,
+    /// Display term, role and category of each phrase.
+    pub roles: Vec<(String, Role, Option<Aspect>)>,
+    /// Every word used in a phrase.
+    pub words: HashSet<String>,
+}
+
+impl Lexicon {
+    /// Parse a lexicon. `json` selects JSON, otherwise TOML is expected.
+    pub fn parse(source: &str, json: bool) -> Result<Self, String> {
+        let file: LexiconFile = if json {
+            serde_json::from_str(source).map_err(|e| format!("Invalid lexicon JSON: {}", e))?
+        } else {
+            toml::from_str(source).map_err(|e| format!("Invalid lexicon TOML: {}", e))?
+        };
+        Self::compile(file)
+    }
+
+    /// Load a lexicon file; the format follows the extension (`.json` or TOML).
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read lexicon {}: {}", path.display(), e))?;
+        let json = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+        Self::parse(&source, json).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// The lexicon compiled into the binary for `language`.
+    pub fn builtin(language: Language) -> Self {
+        Self::parse(builtin_source(language), false).expect("built-in lexicon is valid")
+    }
+
+    fn compile(file: LexiconFile) -> Result<Self, String> {
+        let language = Language::from_code(&file.language)
+            .ok_or_else(|| format!("Unsupported lexicon language {:?}", file.language))?;
+
+        let mut seen = HashSet::new();
+        let mut phrases = Vec::new();
+        let mut roles = Vec::new();
+
+        let mut add = |word: &str, role: Role, category: Option<Aspect>| -> Result<(), String> {
+            let words = phrase_words(word);
+            if words.is_empty() {
+                return Err(format!("Term {:?} contains no words", word));
+            }
+            if !seen.insert(words.clone()) {
+                return Err(format!("Duplicate term {:?}", word));
+            }
+            phrases.push(words);
+            roles.push((word.to_lowercase(), role, category));
+            Ok(())
+        };
+
+        for entry in &file.terms {
+            check_weight(&entry.word, entry.weight)?;
+            let signed = match entry.polarity {
+                Polarity::Positive => entry.weight,
+                Polarity::Negative => -entry.weight,
+            };
+            add(&entry.word, Role::Sentiment(signed), entry.category)?;
+        }
+
+        let modifiers = &file.modifiers;
+        for word in &modifiers.negators {
+            add(word, Role::Negator, None)?;
+        }
+        for word in &modifiers.post_negators {
+            add(word, Role::PostNegator, None)?;
+        }
+        for word in &modifiers.contrasts {
+            add(word, Role::Contrast, None)?;
+        }
+        for (word, multiplier) in modifiers.intensifiers.iter().chain(&modifiers.dampeners) {
+            check_weight(word, *multiplier)?;
+            add(word, Role::Modifier(*multiplier), None)?;
+        }
+        for (aspect, words) in &file.aspects {
+            for word in words {
+                add(word, Role::Aspect(*aspect), Some(*aspect))?;
+            }
+        }
+
+        if file.terms.is_empty() {
+            return Err("Lexicon has no terms".to_string());
+        }
+
+        Ok(Lexicon {
+            version: file.version,
+            language,
+            stems: file.stems,
+            stopwords: file.stopwords.iter().map(|w| w.to_lowercase()).collect(),
+            entries: file.terms,
+            words: phrases.iter().flatten().cloned().collect(),
+            phrases,
+            roles,
+        })
+    }
+}
+
+fn check_weight(word: &str, weight: f64) -> Result<(), String> {
+    if weight.is_finite() && weight > 0.0 && weight <= MAX_WEIGHT {
+        Ok(())
+    } else {
+        Err(format!("Term {:?} has weight {} (expected 0 < weight <= {})", word, weight, MAX_WEIGHT))
+    }
+}
+
+/// Holds the active lexicon and, when it came from a file, reloads it
+/// whenever the file changes. Readers always see a complete lexicon: a
+/// reload that fails validation, or whose file declares another language,
+/// keeps the previous one.
+pub struct LexiconStore {
+    language: Language,
+    path: Option<PathBuf>,
+    current: RwLock<Arc<Lexicon>>,
+    modified: RwLock<Option<SystemTime>>,
+}
+
+impl LexiconStore {
+    /// A store backed by a built-in lexicon; `reload` is a no-op.
+    pub fn builtin(language: Language) -> Self {
+        LexiconStore {
+            language,
+            path: None,
+            current: RwLock::new(Arc::new(Lexicon::builtin(language))),
+            modified: RwLock::new(None),
+        }
+    }
+
+    /// Load and validate the lexicon at `path`, which must be for
+    /// `language`.
+    pub fn open(path: PathBuf, language: Language) -> Result<Self, String> {
+        let modified = modified_time(&path);
+        let lexicon = load_for(&path, language)?;
+        Ok(LexiconStore {
+            language,
+            path: Some(path),
+            current: RwLock::new(Arc::new(lexicon)),
+            modified: RwLock::new(modified),
+        })
+    }
+
+    pub fn current(&self) -> Arc<Lexicon> {
+        self.current.read().expect("lexicon lock poisoned").clone()
+    }
+
+    /// Re-read the file if its modification time changed.
+    /// Returns `Ok(true)` when a new lexicon was swapped in.
+    pub fn reload_if_changed(&self) -> Result<bool, String> {
+        let Some(path) = &self.path else { return Ok(false) };
+        let modified = modified_time(path);
+        if modified == *self.modified.read().expect("lexicon lock poisoned") {
+            return Ok(false);
+        }
+        *self.modified.write().expect("lexicon lock poisoned") = modified;
+        self.reload().map(|_| true)
+    }
+
+    /// Unconditionally re-read the file.
+    pub fn reload(&self) -> Result<(), String> {
+        let Some(path) = &self.path else { return Ok(()) };
+        let lexicon = load_for(path, self.language)?;
+        *self.current.write().expect("lexicon lock poisoned") = Arc::new(lexicon);
+        Ok(())
+    }
+}
+
+/// Load the lexicon at `path`, rejecting one declaring another language.
+fn load_for(path: &Path, language: Language) -> Result<Lexicon, String> {
+    let lexicon = Lexicon::load(path)?;
+    if lexicon.language != language {
+        return Err(format!(
+            "Lexicon {} for {} declares language {:?}",
+            path.display(),
+            language.code(),
+            lexicon.language.code()
+        ));
+    }
+    Ok(lexicon)
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+static STORES: OnceCell<HashMap<Language, LexiconStore>> = OnceCell::new();
+
+/// Find `<dir>/<code>.toml` or `<dir>/<code>.json`.
+fn lexicon_file(dir: &Path, language: Language) -> Option<PathBuf> {
+    ["toml", "json"]
+        .iter()
+        .map(|ext| dir.join(format!("{}.{}", language.code(), ext)))
+        .find(|path| path.is_file())
+}
+
+/// Load one lexicon per supported language, validating each. Files come
+/// from `SENTIMENT_LEXICON_DIR` (`en.toml`, `tr.json`, ...); languages
+/// without a file use the built-in lexicon. Call once at startup; an
+/// invalid file is a boot error.
+pub fn init_lexicons() -> Result<(), String> {
+    let dir = std::env::var("SENTIMENT_LEXICON_DIR")
+        .ok()
+        .filter(|d| !d.trim().is_empty())
+        .map(|d| PathBuf::from(d.trim()));
+
+    let mut stores = HashMap::new();
+    for language in Language::ALL {
+        let store = match dir.as_deref().and_then(|d| lexicon_file(d, language)) {
+            Some(path) => LexiconStore::open(path, language)?,
+            None => LexiconStore::builtin(language),
+        };
+
+        let lexicon = store.current();
+        log::info!(
+            "Sentiment lexicon {} {} ({} terms) from {}",
+            language.code(),
+            lexicon.version,
+            lexicon.entries.len(),
+            store.path.as_ref().map_or("built-in".to_string(), |p| p.display().to_string()),
+        );
+        stores.insert(language, store);
+    }
+
+    STORES
+        .set(stores)
+        .map_err(|_| "Sentiment lexicons already initialized".to_string())
+}
+
+/// Get the shared lexicon store for a language, falling back to the
+/// built-in lexicons if `init_lexicons()` was never called.
+pub fn store(language: Language) -> &'static LexiconStore {
+    let stores = STORES.get_or_init(|| {
+        Language::ALL.into_iter().map(|l| (l, LexiconStore::builtin(l))).collect()
+    });
+    &stores[&language]
+}
+
+/// Poll the lexicon files and hot-swap any that change. The interval is
+/// `SENTIMENT_LEXICON_RELOAD_SECS` (default 30, `0` disables reloading).
+pub fn spawn_reload_watcher() {
+    let secs = std::env::var("SENTIMENT_LEXICON_RELOAD_SECS")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(30);
+    if secs == 0 || Language::ALL.iter().all(|l| store(*l).path.is_none()) {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(secs));
+        loop {
+            interval.tick().await;
+            for language in Language::ALL {
+                match store(language).reload_if_changed() {
+                    Ok(true) => {
+                        let lexicon = store(language).current();
+                        log::info!(
+                            "✅ Reloaded sentiment lexicon {} {} ({} terms)",
+                            language.code(),
+                            lexicon.version,
+                            lexicon.entries.len()
+                        );
+                    }
+                    Ok(false) => {}
+                    Err(e) => log::error!("❌ Lexicon reload failed, keeping previous lexicon: {}", e),
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_lexicons_are_valid() {
+        for language in Language::ALL {
+            let lexicon = Lexicon::builtin(language);
+            assert_eq!(lexicon.language, language);
+            assert!(!lexicon.stopwords.is_empty());
+        }
+        assert!(Lexicon::builtin(Language::English).entries.iter().any(|e| e.word == "food poisoning"));
+        assert!(Lexicon::builtin(Language::Turkish).stems);
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let json = r#"{
+            "version": "test",
+            "terms": [
+                { "word": "yummy", "polarity": "positive", "weight": 2.0, "category": "food" },
+                { "word": "meh", "polarity": "negative" }
+            ]
+        }"#;
+        let lexicon = Lexicon::parse(json, true).unwrap();
+        assert_eq!(lexicon.version, "test");
+        assert_eq!(lexicon.roles[0].1, Role::Sentiment(2.0));
+        assert_eq!(lexicon.roles[1].1, Role::Sentiment(-1.0));
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        let bad_category = "[[terms]]\nword = \"yummy\"\npolarity = \"positive\"\ncategory = \"parking\"";
+        assert!(Lexicon::parse(bad_category, false).unwrap_err().contains("parking"));
+
+        let bad_weight = "[[terms]]\nword = \"yummy\"\npolarity = \"positive\"\nweight = -1";
+        assert!(Lexicon::parse(bad_weight, false).unwrap_err().contains("weight"));
+
+        let duplicate = "[[terms]]\nword = \"Top-notch\"\npolarity = \"positive\"\n\n[[terms]]\nword = \"top notch\"\npolarity = \"positive\"";
+        assert!(Lexicon::parse(duplicate, false).unwrap_err().contains("Duplicate"));
+
+        let bad_polarity = "[[terms]]\nword = \"yummy\"\npolarity = \"great\"";
+        assert!(Lexicon::parse(bad_polarity, false).is_err());
+
+        assert!(Lexicon::parse("terms = []", false).is_err());
+
+        let bad_language = "language = \"it\"\n[[terms]]\nword = \"buono\"\npolarity = \"positive\"";
+        assert!(Lexicon::parse(bad_language, false).unwrap_err().contains("Unsupported"));
+    }
+
+    #[test]
+    fn test_parse_aspects() {
+        let toml = "[aspects]\nservice = [\"waiter\"]\n\n[[terms]]\nword = \"slow\"\npolarity = \"negative\"\ncategory = \"service\"";
+        let lexicon = Lexicon::parse(toml, false).unwrap();
+        assert_eq!(lexicon.roles[0].2, Some(Aspect::Service));
+        assert_eq!(lexicon.roles[1], ("waiter".to_string(), Role::Aspect(Aspect::Service), Some(Aspect::Service)));
+
+        let unknown = "[aspects]\nparking = [\"valet\"]\n\n[[terms]]\nword = \"slow\"\npolarity = \"negative\"";
+        assert!(Lexicon::parse(unknown, false).is_err());
+    }
+
+    #[test]
+    fn test_store_reload_keeps_previous_lexicon_on_error() {
+        let path = std::env::temp_dir().join(format!("dene-lexicon-{}.toml", std::process::id()));
+        std::fs::write(&path, "version = \"1\"\n[[terms]]\nword = \"yummy\"\npolarity = \"positive\"").unwrap();
+        let store = LexiconStore::open(path.clone(), Language::English).unwrap();
+        assert_eq!(store.current().version, "1");
+
+        std::fs::write(&path, "version = \"2\"\n[[terms]]\nword = \"yummy\"\npolarity = \"positive\"").unwrap();
+        store.reload().unwrap();
+        assert_eq!(store.current().version, "2");
+
+        std::fs::write(&path, "version = \"3\"\n[[terms]]\nword = \"\"\npolarity = \"positive\"").unwrap();
+        assert!(store.reload().is_err());
+        assert_eq!(store.current().version, "2");
+
+        std::fs::write(&path, "version = \"4\"\nlanguage = \"de\"\n[[terms]]\nword = \"lecker\"\npolarity = \"positive\"").unwrap();
+        assert!(store.reload().is_err());
+        assert_eq!(store.current().version, "2");
+
+        std::fs::remove_file(&path).ok();
+    }
+}
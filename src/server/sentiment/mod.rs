@@ -0,0 +1,529 @@
+//! Pluggable sentiment analysis.
+//!
+//! The CRUD layer only talks to [`analyze()`], which detects the review's
+//! language and hands it to that language's analyzer. Which implementation
+//! sits behind each language is chosen once at startup via the
+//! `SENTIMENT_ANALYZER` env var.
+
+pub mod bayes;
+pub mod corrections;
+pub mod eval;
+pub mod keyphrases;
+pub mod keyword;
+pub mod language;
+pub mod lexicon;
+pub mod signals;
+pub mod tokenizer;
+
+pub use bayes::NaiveBayesModel;
+pub use keyword::KeywordAnalyzer;
+pub use language::Language;
+
+use std::collections::{BTreeMap, HashMap};
+use once_cell::sync::OnceCell;
+use crate::model::{Aspect, AspectSentiment, Keyphrase, SentimentTerm};
+
+/// Sentiment label. Stored on comments as `status`: 1=negative, 2=neutral, 3=positive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SentimentLabel {
+    Negative,
+    Neutral,
+    Positive,
+}
+
+impl SentimentLabel {
+    /// In status order.
+    pub const ALL: [SentimentLabel; 3] = [SentimentLabel::Negative, SentimentLabel::Neutral, SentimentLabel::Positive];
+
+    pub fn status(self) -> u8 {
+        match self {
+            SentimentLabel::Negative => 1,
+            SentimentLabel::Neutral => 2,
+            SentimentLabel::Positive => 3,
+        }
+    }
+
+    pub fn from_status(status: u8) -> Self {
+        match status {
+            1 => SentimentLabel::Negative,
+            3 => SentimentLabel::Positive,
+            _ => SentimentLabel::Neutral,
+        }
+    }
+
+    /// Label for a signed total of evidence weights.
+    pub fn from_weight(weight: f64) -> Self {
+        if weight < -f64::EPSILON {
+            SentimentLabel::Negative
+        } else if weight > f64::EPSILON {
+            SentimentLabel::Positive
+        } else {
+            SentimentLabel::Neutral
+        }
+    }
+
+    /// Parse "negative", "neutral" or "positive" (any case).
+    pub fn parse(label: &str) -> Option<Self> {
+        SentimentLabel::ALL.into_iter().find(|l| l.as_str().eq_ignore_ascii_case(label.trim()))
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SentimentLabel::Negative => "negative",
+            SentimentLabel::Neutral => "neutral",
+            SentimentLabel::Positive => "positive",
+        }
+    }
+}
+
+/// A lexicon term (or model feature) that contributed to a verdict.
+/// Negative weights pull towards negative, positive towards positive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Evidence {
+    pub term: String,
+    pub weight: f64,
+    /// Aspect the term was said about, if any.
+    pub aspect: Option<Aspect>,
+    /// Byte range of the term in the analyzed text.
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Outcome of analyzing a piece of text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SentimentResult {
+    pub label: SentimentLabel,
+    /// 0–100, 50 = neutral, <50 = negative leaning, >50 = positive leaning.
+    pub score: u8,
+    /// 0.0–1.0, how strongly the evidence agrees on the label. Text with
+    /// no sentiment evidence at all is confidently neutral (1.0); evenly
+    /// balanced evidence is a neutral guess (0.0).
+    pub confidence: f32,
+    pub evidence: Vec<Evidence>,
+}
+
+impl SentimentResult {
+    /// The label as the numeric `status` stored on comments.
+    pub fn status(&self) -> u8 {
+        self.label.status()
+    }
+
+    /// Net sentiment per aspect mentioned, in `Aspect` order.
+    pub fn aspects(&self) -> Vec<AspectSentiment> {
+        let mut totals: BTreeMap<Aspect, f64> = BTreeMap::new();
+        for evidence in &self.evidence {
+            if let Some(aspect) = evidence.aspect {
+                *totals.entry(aspect).or_default() += evidence.weight;
+            }
+        }
+        totals
+            .into_iter()
+            .map(|(aspect, weight)| AspectSentiment {
+                aspect,
+                status: SentimentLabel::from_weight(weight).status(),
+                weight,
+            })
+            .collect()
+    }
+
+    /// Label, score and confidence from signed evidence over a review of
+    /// `word_count` words: the label follows the sign of the total, the
+    /// score its density (50 = neutral), the confidence how one-sided it is
+    /// (1.0 when there is no evidence to disagree).
+    pub fn from_evidence(evidence: Vec<Evidence>, word_count: usize) -> Self {
+        let total: f64 = evidence.iter().map(|e| e.weight).sum();
+        let magnitude: f64 = evidence.iter().map(|e| e.weight.abs()).sum();
+
+        let ratio = total / word_count.max(1) as f64;
+        let score = (50.0 + ratio * 100.0).clamp(0.0, 100.0) as u8;
+        let confidence = if magnitude > 0.0 { (total.abs() / magnitude) as f32 } else { 1.0 };
+
+        SentimentResult { label: SentimentLabel::from_weight(total), score, confidence, evidence }
+    }
+
+    /// Overall verdict of a review from its sentences. Each sentence votes
+    /// for its label with its length times its confidence, so a long,
+    /// one-sided complaint outweighs a short aside; the score is the mean of
+    /// the sentence scores weighted by length. Sentences without any
+    /// sentiment ("We came on Tuesday.") do not dilute the confidence.
+    pub fn combine(sentences: &[Sentence]) -> Self {
+        if let [only] = sentences {
+            return only.result.clone();
+        }
+
+        let words: f64 = sentences.iter().map(|s| s.words.max(1) as f64).sum();
+        if words == 0.0 {
+            return SentimentResult::unanalyzed();
+        }
+        let mut vote = 0.0;
+        let mut score = 0.0;
+        let mut opinionated = 0.0;
+        for sentence in sentences {
+            let length = sentence.words.max(1) as f64;
+            let direction = sentence.result.status() as f64 - 2.0;
+            vote += direction * sentence.result.confidence as f64 * length;
+            score += sentence.result.score as f64 * length;
+            if direction != 0.0 || !sentence.result.evidence.is_empty() {
+                opinionated += length;
+            }
+        }
+
+        SentimentResult {
+            label: SentimentLabel::from_weight(vote),
+            score: (score / words).round() as u8,
+            confidence: if opinionated > 0.0 { (vote.abs() / opinionated) as f32 } else { 1.0 },
+            evidence: sentences.iter().flat_map(|s| s.result.evidence.iter().cloned()).collect(),
+        }
+    }
+
+    /// Placeholder for text no analyzer could read.
+    pub fn unanalyzed() -> Self {
+        SentimentResult {
+            label: SentimentLabel::Neutral,
+            score: 50,
+            confidence: 0.0,
+            evidence: Vec::new(),
+        }
+    }
+}
+
+impl From<&Evidence> for SentimentTerm {
+    fn from(evidence: &Evidence) -> Self {
+        SentimentTerm { term: evidence.term.clone(), weight: evidence.weight, start: evidence.start, end: evidence.end }
+    }
+}
+
+impl From<signals::Symbol> for Evidence {
+    fn from(symbol: signals::Symbol) -> Self {
+        Evidence { term: symbol.text, weight: symbol.weight, aspect: None, start: symbol.start, end: symbol.end }
+    }
+}
+
+/// Sentiment of one sentence of a review.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sentence {
+    /// Byte range of the sentence in the analyzed text.
+    pub start: usize,
+    pub end: usize,
+    /// Words, emoji and emoticons in the sentence: its weight in the
+    /// overall verdict.
+    pub words: usize,
+    /// Verdict on the sentence alone; evidence spans are relative to the
+    /// whole text.
+    pub result: SentimentResult,
+}
+
+/// Words plus emoji and emoticons, the length scores are measured against.
+fn word_count(text: &str) -> usize {
+    tokenizer::tokenize(text).len() + signals::find_symbols(text).len()
+}
+
+/// Score each sentence of `text` on its own with `analyze`; combine them
+/// with [`SentimentResult::combine`].
+pub fn analyze_sentences(text: &str, analyze: impl Fn(&str) -> SentimentResult) -> Vec<Sentence> {
+    tokenizer::sentences(text)
+        .into_iter()
+        .map(|range| {
+            let sentence = &text[range.clone()];
+            let mut result = analyze(sentence);
+            for evidence in &mut result.evidence {
+                evidence.start += range.start;
+                evidence.end += range.start;
+            }
+            Sentence { start: range.start, end: range.end, words: word_count(sentence), result }
+        })
+        .collect()
+}
+
+/// Judge `text` on its emoji and emoticons alone.
+fn analyze_symbols(text: &str) -> SentimentResult {
+    let evidence = signals::find_symbols(text).into_iter().map(Evidence::from).collect();
+    SentimentResult::from_evidence(evidence, word_count(text))
+}
+
+/// Version recorded for reviews judged on emoji and emoticons alone.
+const SYMBOLS_VERSION: &str = "symbols";
+
+/// A review routed through language detection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Analysis {
+    /// `None` when the language could not be detected.
+    pub language: Option<Language>,
+    /// `SentimentAnalyzer::version` of the analyzer that produced the
+    /// result; `None` when nothing could be read and the result is a
+    /// neutral placeholder rather than a real verdict.
+    pub analyzer_version: Option<String>,
+    /// Combined verdict of the sentences.
+    pub result: SentimentResult,
+    /// Per-sentence verdicts, in text order.
+    pub sentences: Vec<Sentence>,
+    /// Keyphrases of each sentence with its label; empty when the language
+    /// was not detected.
+    pub keyphrases: Vec<Keyphrase>,
+}
+
+impl Analysis {
+    pub fn is_analyzed(&self) -> bool {
+        self.analyzer_version.is_some()
+    }
+}
+
+/// A sentiment model. Implementations must be cheap to share across
+/// Actix workers, so analysis takes `&self`.
+pub trait SentimentAnalyzer: Send + Sync {
+    /// Short identifier used for selection and logging.
+    fn name(&self) -> &'static str;
+
+    /// Identifies the analyzer together with the lexicon or model it is
+    /// using, so comments scored by an older one can be found and re-run.
+    fn version(&self) -> String {
+        self.name().to_string()
+    }
+
+    fn analyze(&self, text: &str) -> SentimentResult;
+}
+
+static ANALYZERS: OnceCell<HashMap<Language, Box<dyn SentimentAnalyzer>>> = OnceCell::new();
+
+/// Build an analyzer by name for one language. `naive_bayes` loads the
+/// language's model from `SENTIMENT_MODEL_DIR` and falls back to the
+/// keyword analyzer when there is no model file.
+pub fn build_analyzer(name: &str, language: Language) -> Result<Box<dyn SentimentAnalyzer>, String> {
+    match name {
+        "keyword" => Ok(Box::new(KeywordAnalyzer::new(language))),
+        "naive_bayes" => {
+            let dir = bayes::model_dir();
+            match bayes::load_model(&dir, language)? {
+                Some(model) => Ok(Box::new(model)),
+                None => {
+                    log::warn!(
+                        "No sentiment model at {}, using keyword analyzer for {}",
+                        bayes::model_path(&dir, language).display(),
+                        language.code()
+                    );
+                    Ok(Box::new(KeywordAnalyzer::new(language)))
+                }
+            }
+        }
+        other => Err(format!("Unknown sentiment analyzer: {}", other)),
+    }
+}
+
+fn default_analyzers() -> HashMap<Language, Box<dyn SentimentAnalyzer>> {
+    Language::ALL
+        .into_iter()
+        .map(|l| (l, Box::new(KeywordAnalyzer::new(l)) as Box<dyn SentimentAnalyzer>))
+        .collect()
+}
+
+/// Load the lexicons and select the analyzer named by `SENTIMENT_ANALYZER`
+/// (default: `keyword`) for every supported language. Call once at
+/// startup, before the server accepts requests.
+pub fn init_analyzer() -> Result<(), String> {
+    lexicon::init_lexicons()?;
+
+    let name = std::env::var("SENTIMENT_ANALYZER").unwrap_or_else(|_| "keyword".to_string());
+    let mut analyzers = HashMap::new();
+    for language in Language::ALL {
+        let analyzer = build_analyzer(name.trim(), language)?;
+        log::info!("Sentiment analyzer {}: {}", language.code(), analyzer.name());
+        analyzers.insert(language, analyzer);
+    }
+
+    ANALYZERS
+        .set(analyzers)
+        .map_err(|_| "Sentiment analyzer already initialized".to_string())
+}
+
+/// Get the configured analyzer for a language, falling back to the keyword
+/// analyzer if `init_analyzer()` was never called.
+pub fn analyzer_for(language: Language) -> &'static dyn SentimentAnalyzer {
+    ANALYZERS.get_or_init(default_analyzers)[&language].as_ref()
+}
+
+/// Detect the language of `text` and analyze it sentence by sentence with
+/// that language's analyzer. Text in an unsupported language is not
+/// guessed at: it is judged on its emoji and emoticons alone, or gets a
+/// neutral placeholder when it has none.
+pub fn analyze(text: &str) -> Analysis {
+    let (language, analyzer_version, sentences) = match language::detect(text) {
+        Some(language) => {
+            let analyzer = analyzer_for(language);
+            (Some(language), analyzer.version(), analyze_sentences(text, |s| analyzer.analyze(s)))
+        }
+        None if signals::find_symbols(text).is_empty() => {
+            return Analysis {
+                language: None,
+                analyzer_version: None,
+                result: SentimentResult::unanalyzed(),
+                sentences: Vec::new(),
+                keyphrases: Vec::new(),
+            };
+        }
+        None => (None, SYMBOLS_VERSION.to_string(), analyze_sentences(text, analyze_symbols)),
+    };
+    let keyphrases = match language {
+        Some(language) => sentence_keyphrases(text, &sentences, language),
+        None => Vec::new(),
+    };
+    Analysis {
+        language,
+        analyzer_version: Some(analyzer_version),
+        result: SentimentResult::combine(&sentences),
+        sentences,
+        keyphrases,
+    }
+}
+
+/// Keyphrases of each sentence, labelled with the sentence's verdict. A
+/// phrase repeated under the same label is listed once.
+fn sentence_keyphrases(text: &str, sentences: &[Sentence], language: Language) -> Vec<Keyphrase> {
+    let mut keyphrases: Vec<Keyphrase> = Vec::new();
+    for sentence in sentences {
+        for phrase in keyphrases::extract(&text[sentence.start..sentence.end], language) {
+            let keyphrase = Keyphrase { phrase, status: sentence.result.status() };
+            if !keyphrases.contains(&keyphrase) {
+                keyphrases.push(keyphrase);
+            }
+        }
+    }
+    keyphrases
+}
+
+/// Sentiment label of `text` in any supported language.
+/// Returns: 1 = negative, 2 = neutral, 3 = positive.
+pub fn analyze_sentiment(text: &str) -> u8 {
+    analyze(text).result.status()
+}
+
+/// Sentiment score of `text` from 0–100.
+pub fn compute_scoring(text: &str) -> u8 {
+    analyze(text).result.score
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negative_sentiment() {
+        assert_eq!(analyze_sentiment("This food was terrible and disgusting"), 1);
+    }
+
+    #[test]
+    fn test_positive_sentiment() {
+        assert_eq!(analyze_sentiment("The food was excellent and amazing!"), 3);
+    }
+
+    #[test]
+    fn test_neutral_sentiment() {
+        assert_eq!(analyze_sentiment("I had dinner here last night"), 2);
+    }
+
+    #[test]
+    fn test_scoring() {
+        let score = compute_scoring("terrible food, very bad");
+        assert!(score < 50);
+        let score = compute_scoring("excellent and amazing food");
+        assert!(score > 50);
+    }
+
+    #[test]
+    fn test_build_analyzer_rejects_unknown_name() {
+        assert!(build_analyzer("keyword", Language::English).is_ok());
+        assert!(build_analyzer("gpt", Language::English).is_err());
+    }
+
+    #[test]
+    fn test_naive_bayes_falls_back_without_model() {
+        let dir = bayes::model_dir();
+        assert!(!bayes::model_path(&dir, Language::German).exists());
+        assert_eq!(build_analyzer("naive_bayes", Language::German).unwrap().name(), "keyword");
+    }
+
+    #[test]
+    fn test_analyze_routes_by_language() {
+        let analysis = analyze("Das Essen war ausgezeichnet");
+        assert_eq!(analysis.language, Some(Language::German));
+        assert_eq!(analysis.result.label, SentimentLabel::Positive);
+
+        let analysis = analyze("Le service était lent et impoli");
+        assert_eq!(analysis.language, Some(Language::French));
+        assert!(analysis.analyzer_version.unwrap().starts_with("keyword/fr/"));
+        assert_eq!(analysis.result.label, SentimentLabel::Negative);
+    }
+
+    #[test]
+    fn test_unknown_language_is_judged_on_emoji() {
+        let analysis = analyze("🙄 :(");
+        assert_eq!(analysis.language, None);
+        assert!(analysis.is_analyzed());
+        assert_eq!(analysis.result.label, SentimentLabel::Negative);
+        assert!(analysis.result.score < 50);
+    }
+
+    #[test]
+    fn test_sentences_are_scored_separately() {
+        let text = "The terrace is lovely. But the soup was cold, the bread was stale and the waiter was rude!";
+        let analysis = analyze(text);
+        let labels: Vec<_> = analysis.sentences.iter().map(|s| s.result.label).collect();
+        assert_eq!(labels, [SentimentLabel::Positive, SentimentLabel::Negative]);
+        assert_eq!(&text[analysis.sentences[0].start..analysis.sentences[0].end], "The terrace is lovely.");
+
+        // evidence points into the whole text
+        let rude = analysis.result.evidence.iter().find(|e| e.term == "rude").unwrap();
+        assert_eq!(&text[rude.start..rude.end], "rude");
+
+        // the longer complaint outweighs the aside
+        assert_eq!(analysis.result.label, SentimentLabel::Negative);
+        assert!(analysis.result.score < 50);
+
+        // keyphrases carry their own sentence's label
+        let status = |phrase: &str| analysis.keyphrases.iter().find(|k| k.phrase == phrase).map(|k| k.status);
+        assert_eq!(status("terrace"), Some(3));
+        assert_eq!(status("soup"), Some(1));
+    }
+
+    #[test]
+    fn test_combine_weighs_sentences_by_length_and_confidence() {
+        let evidence = |weights: &[f64]| {
+            weights
+                .iter()
+                .map(|w| Evidence { term: "t".to_string(), weight: *w, aspect: None, start: 0, end: 0 })
+                .collect::<Vec<_>>()
+        };
+        let sentence = |words, label, confidence, score, weights: &[f64]| Sentence {
+            start: 0,
+            end: 0,
+            words,
+            result: SentimentResult { label, score, confidence, evidence: evidence(weights) },
+        };
+        let sentences = [
+            sentence(4, SentimentLabel::Positive, 1.0, 75, &[1.0]),
+            sentence(6, SentimentLabel::Negative, 0.5, 40, &[-1.5, 0.5]),
+            sentence(10, SentimentLabel::Neutral, 0.0, 50, &[1.0, -1.0]),
+        ];
+        let result = SentimentResult::combine(&sentences);
+        // 4 × 1.0 for positive against 6 × 0.5 for negative
+        assert_eq!(result.label, SentimentLabel::Positive);
+        assert_eq!(result.score, 52);
+        assert!((result.confidence - 0.05).abs() < 1e-6);
+
+        // a sentence without evidence neither votes nor dilutes
+        let plain = sentence(10, SentimentLabel::Neutral, 1.0, 50, &[]);
+        let result = SentimentResult::combine(&[sentences[0].clone(), plain.clone()]);
+        assert_eq!(result.label, SentimentLabel::Positive);
+        assert_eq!(result.confidence, 1.0);
+        assert_eq!(SentimentResult::combine(&[plain.clone(), plain]).confidence, 1.0);
+
+        assert_eq!(SentimentResult::combine(&sentences[1..2]), sentences[1].result);
+        assert_eq!(SentimentResult::combine(&[]), SentimentResult::unanalyzed());
+    }
+
+    #[test]
+    fn test_unknown_language_is_flagged_unanalyzed() {
+        let analysis = analyze("Cibo ottimo e servizio veloce");
+        assert!(!analysis.is_analyzed());
+        assert_eq!(analysis.result, SentimentResult::unanalyzed());
+    }
+}
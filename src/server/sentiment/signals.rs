@@ -0,0 +1,160 @@
+//! Emoji and emoticon sentiment signals, shared by every language.
+//!
+//! The polarity table lives in `data/lexicon/emoji.toml` and is compiled
+//! into the binary.
+
+use std::collections::HashMap;
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use unicode_segmentation::UnicodeSegmentation;
+
+static TABLE: Lazy<SymbolTable> = Lazy::new(|| {
+    SymbolTable::parse(include_str!("../../../data/lexicon/emoji.toml")).expect("built-in emoji table is valid")
+});
+
+/// Characters an emoticon may be glued to the end of a word with ("great:)").
+const EMOTICON_EYES: &[char] = &[':', ';', '='];
+
+/// Characters allowed right after an emoticon.
+const EMOTICON_FOLLOWERS: &[char] = &['.', ',', '!', '?', ';'];
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SymbolFile {
+    #[serde(default)]
+    emoji: HashMap<String, f64>,
+    #[serde(default)]
+    emoticons: HashMap<String, f64>,
+}
+
+/// An emoji or emoticon occurrence.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    /// The table entry that matched: emoji without skin tone or variation
+    /// selector, emoticons without repeated mouths (":)))" → ":)").
+    pub text: String,
+    /// Signed polarity from the table.
+    pub weight: f64,
+    /// Byte range in the text, including any repeats.
+    pub start: usize,
+    pub end: usize,
+}
+
+struct SymbolTable {
+    emoji: HashMap<String, f64>,
+    /// Longest first, so ":-(" wins over ":-".
+    emoticons: Vec<(String, f64)>,
+}
+
+/// Skin-tone modifiers and the emoji presentation selector do not change
+/// an emoji's meaning.
+fn strip_modifiers(grapheme: &str) -> String {
+    grapheme
+        .chars()
+        .filter(|c| *c != '\u{FE0F}' && !('\u{1F3FB}'..='\u{1F3FF}').contains(c))
+        .collect()
+}
+
+impl SymbolTable {
+    fn parse(source: &str) -> Result<Self, String> {
+        let file: SymbolFile = toml::from_str(source).map_err(|e| format!("Invalid emoji table: {}", e))?;
+        for (symbol, weight) in file.emoji.iter().chain(&file.emoticons) {
+            if symbol.trim().is_empty() || !weight.is_finite() || *weight == 0.0 || weight.abs() > 10.0 {
+                return Err(format!("Invalid emoji table entry {:?} = {}", symbol, weight));
+            }
+        }
+
+        let emoji = file.emoji.into_iter().map(|(e, w)| (strip_modifiers(&e), w)).collect();
+        let mut emoticons: Vec<(String, f64)> = file.emoticons.into_iter().collect();
+        emoticons.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+        Ok(SymbolTable { emoji, emoticons })
+    }
+
+    /// The emoticon starting at byte `start`, if it stands apart from the
+    /// surrounding text, with the end of its span.
+    fn emoticon_at(&self, text: &str, start: usize) -> Option<(&str, f64, usize)> {
+        let prev = text[..start].chars().next_back();
+        for (emoticon, weight) in &self.emoticons {
+            if !text[start..].starts_with(emoticon.as_str()) {
+                continue;
+            }
+            let glued = prev.is_some_and(|c| !c.is_whitespace());
+            if glued && !emoticon.starts_with(EMOTICON_EYES) {
+                continue;
+            }
+
+            let mut end = start + emoticon.len();
+            if let Some(mouth) = emoticon.chars().next_back() {
+                while text[end..].starts_with(mouth) {
+                    end += mouth.len_utf8();
+                }
+            }
+            if text[end..].chars().next().is_none_or(|c| c.is_whitespace() || EMOTICON_FOLLOWERS.contains(&c)) {
+                return Some((emoticon, *weight, end));
+            }
+        }
+        None
+    }
+
+    fn find(&self, text: &str) -> Vec<Symbol> {
+        let mut symbols = Vec::new();
+        let mut skip_until = 0;
+        for (start, grapheme) in text.grapheme_indices(true) {
+            if start < skip_until {
+                continue;
+            }
+            let key = strip_modifiers(grapheme);
+            if let Some(weight) = self.emoji.get(&key) {
+                symbols.push(Symbol { text: key, weight: *weight, start, end: start + grapheme.len() });
+            } else if let Some((emoticon, weight, end)) = self.emoticon_at(text, start) {
+                symbols.push(Symbol { text: emoticon.to_string(), weight, start, end });
+                skip_until = end;
+            }
+        }
+        symbols
+    }
+}
+
+/// Find emoji and emoticons with a known polarity, in text order.
+pub fn find_symbols(text: &str) -> Vec<Symbol> {
+    TABLE.find(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(text: &str) -> Vec<(String, f64)> {
+        find_symbols(text).into_iter().map(|s| (s.text, s.weight)).collect()
+    }
+
+    #[test]
+    fn test_finds_emoji() {
+        assert_eq!(found("sooo goood 😍😍"), [("😍".to_string(), 2.0), ("😍".to_string(), 2.0)]);
+        // skin tone and variation selector are ignored
+        assert_eq!(found("👍🏽 ❤️"), [("👍".to_string(), 1.0), ("❤".to_string(), 1.5)]);
+        assert!(found("plain text").is_empty());
+    }
+
+    #[test]
+    fn test_finds_emoticons() {
+        assert_eq!(found("meh 🙄 :("), [("🙄".to_string(), -1.0), (":(".to_string(), -1.0)]);
+        assert_eq!(found("great:))) <3"), [(":)".to_string(), 1.0), ("<3".to_string(), 1.5)]);
+        assert_eq!(found(":-( bad"), [(":-(".to_string(), -1.0)]);
+        let symbols = find_symbols("ok :)))!");
+        assert_eq!((symbols[0].start, symbols[0].end), (3, 7));
+    }
+
+    #[test]
+    fn test_ignores_emoticon_lookalikes() {
+        assert!(found("see http://example.com").is_empty());
+        assert!(found("open 10:30 to 22:00").is_empty());
+        assert!(found("x<3y").is_empty());
+    }
+
+    #[test]
+    fn test_rejects_bad_entries() {
+        assert!(SymbolTable::parse("[emoji]\n\"😍\" = 0").is_err());
+        assert!(SymbolTable::parse("[emoticons]\n\" \" = 1").is_err());
+    }
+}
@@ -0,0 +1,260 @@
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Punctuation that ends a clause, and with it any negation scope.
+const CLAUSE_PUNCTUATION: &[char] = &['.', ',', ';', ':', '!', '?', '(', ')', '…', '—'];
+
+/// French elided articles and pronouns ("l'accueil", "c'était"). The part
+/// after the apostrophe is the word that carries meaning.
+const ELISIONS: &[&str] = &["l", "d", "j", "m", "n", "s", "t", "c", "qu", "jusqu", "lorsqu", "puisqu"];
+
+/// Strip a French elision prefix, returning the byte offset of the rest.
+fn elision_offset(word: &str) -> usize {
+    word.char_indices()
+        .find(|(_, c)| *c == '\'' || *c == '\u{2019}')
+        .filter(|(i, _)| ELISIONS.contains(&word[..*i].to_lowercase().as_str()))
+        .map(|(i, c)| i + c.len_utf8())
+        .filter(|offset| *offset < word.len())
+        .unwrap_or(0)
+}
+
+/// Lowercase a word. The combining dot left behind by lowercasing Turkish
+/// "İ" is dropped so "İYİ" and "iyi" compare equal.
+fn normalize(word: &str) -> String {
+    word.to_lowercase().replace('\u{307}', "")
+}
+
+/// Letters repeated this often in a row mark an elongated word ("goood").
+const ELONGATION_RUN: usize = 3;
+
+/// Shorten every run of the same letter to at most `max` letters, so
+/// "goood" becomes "good" (`max` 2) or "god" (`max` 1).
+pub fn collapse_runs(word: &str, max: usize) -> String {
+    let mut out = String::with_capacity(word.len());
+    let mut prev = None;
+    let mut run = 0;
+    for c in word.chars() {
+        run = if Some(c) == prev { run + 1 } else { 1 };
+        prev = Some(c);
+        if run <= max || !c.is_alphabetic() {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn is_elongated(word: &str) -> bool {
+    let chars: Vec<char> = word.chars().collect();
+    chars.windows(ELONGATION_RUN).any(|w| w[0].is_alphabetic() && w.iter().all(|c| *c == w[0]))
+}
+
+/// Written in capitals for emphasis ("AWFUL"); single letters like "I" do
+/// not count.
+fn is_shouted(word: &str) -> bool {
+    let letters: Vec<char> = word.chars().filter(|c| c.is_alphabetic()).collect();
+    letters.len() >= 2 && letters.iter().all(|c| c.is_uppercase())
+}
+
+/// A lowercased word with its byte span in the original text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub text: String,
+    pub start: usize,
+    pub end: usize,
+    /// Clause punctuation sits between this token and the previous one.
+    pub boundary_before: bool,
+    /// Letters were stretched ("sooo"); `text` has the runs shortened.
+    pub elongated: bool,
+    /// Written in capitals.
+    pub shouted: bool,
+    /// Exclamation marks between this token and the next one.
+    pub exclamations: usize,
+}
+
+/// Split text into words using Unicode (UAX #29) word boundaries.
+/// Punctuation and whitespace are dropped, so "good!" yields "good" and
+/// "top-notch" yields "top", "notch"; clause punctuation is remembered in
+/// `boundary_before`. French elisions are split off, so "l'accueil" yields
+/// "accueil". Stretched letters are shortened to two ("goood" → "good").
+pub fn tokenize(text: &str) -> Vec<Token> {
+    let mut prev_end = 0;
+    let mut tokens: Vec<Token> = text
+        .unicode_word_indices()
+        .map(|(start, word)| {
+            let boundary_before = text[prev_end..start].contains(CLAUSE_PUNCTUATION);
+            prev_end = start + word.len();
+            let word = &word[elision_offset(word)..];
+            let elongated = is_elongated(word);
+            Token {
+                text: if elongated { collapse_runs(&normalize(word), 2) } else { normalize(word) },
+                start: prev_end - word.len(),
+                end: prev_end,
+                boundary_before,
+                elongated,
+                shouted: is_shouted(word),
+                exclamations: 0,
+            }
+        })
+        .collect();
+
+    let starts: Vec<usize> = tokens.iter().skip(1).map(|t| t.start).chain([text.len()]).collect();
+    for (token, next) in tokens.iter_mut().zip(starts) {
+        token.exclamations = text[token.end..next].matches('!').count();
+    }
+    tokens
+}
+
+/// Byte ranges of the sentences in `text` (UAX #29 sentence boundaries),
+/// trimmed of surrounding whitespace. Blank stretches are skipped.
+pub fn sentences(text: &str) -> Vec<Range<usize>> {
+    text.split_sentence_bound_indices()
+        .filter_map(|(start, sentence)| {
+            let trimmed = sentence.trim_start();
+            let start = start + sentence.len() - trimmed.len();
+            let trimmed = trimmed.trim_end();
+            (!trimmed.is_empty()).then(|| start..start + trimmed.len())
+        })
+        .collect()
+}
+
+/// A phrase occurrence: which phrase matched, and the token range it covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PhraseMatch {
+    pub phrase: usize,
+    pub start: usize,
+    pub len: usize,
+}
+
+/// Shortest lexicon word that may match as a stem.
+const MIN_STEM_CHARS: usize = 3;
+
+fn word_matches(word: &str, token: &str, stems: bool) -> bool {
+    word == token || (stems && word.chars().count() >= MIN_STEM_CHARS && token.starts_with(word))
+}
+
+/// Find non-overlapping occurrences of `phrases` (each a sequence of
+/// lowercased words) in `tokens`. At each position the longest phrase wins,
+/// so "food poisoning" is matched as one phrase rather than as "food".
+///
+/// With `stems`, a phrase word also matches tokens that merely start with
+/// it, so Turkish "iyi" matches "iyiydi"; the longer spelled-out word still
+/// wins, so "uygunsuz" beats "uygun".
+pub fn match_phrases(tokens: &[Token], phrases: &[Vec<String>], stems: bool) -> Vec<PhraseMatch> {
+    let mut matches = Vec::new();
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let best = phrases
+            .iter()
+            .enumerate()
+            .filter(|(_, words)| {
+                !words.is_empty()
+                    && i + words.len() <= tokens.len()
+                    && words.iter().zip(&tokens[i..]).all(|(w, t)| word_matches(w, &t.text, stems))
+            })
+            .max_by_key(|(_, words)| (words.len(), words.iter().map(|w| w.len()).sum::<usize>()));
+
+        match best {
+            Some((phrase, words)) => {
+                matches.push(PhraseMatch { phrase, start: i, len: words.len() });
+                i += words.len();
+            }
+            None => i += 1,
+        }
+    }
+
+    matches
+}
+
+/// Tokenize a lexicon term into the word sequence `match_phrases` expects.
+pub fn phrase_words(term: &str) -> Vec<String> {
+    tokenize(term).into_iter().map(|t| t.text).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(text: &str) -> Vec<String> {
+        tokenize(text).into_iter().map(|t| t.text).collect()
+    }
+
+    #[test]
+    fn test_tokenize_strips_punctuation_and_lowercases() {
+        assert_eq!(words("Good!! The soup, sadly... was COLD."), ["good", "the", "soup", "sadly", "was", "cold"]);
+    }
+
+    #[test]
+    fn test_tokenize_keeps_byte_spans() {
+        let text = "Crème brûlée: divine";
+        let tokens = tokenize(text);
+        assert_eq!(tokens.len(), 3);
+        assert_eq!(&text[tokens[1].start..tokens[1].end], "brûlée");
+        assert_eq!(tokens[2].text, "divine");
+    }
+
+    #[test]
+    fn test_tokenize_marks_clause_boundaries() {
+        let boundaries: Vec<bool> = tokenize("not good, really bad - okay").iter().map(|t| t.boundary_before).collect();
+        assert_eq!(boundaries, [false, false, true, false, false]);
+    }
+
+    #[test]
+    fn test_match_phrases_prefers_longest() {
+        let phrases = vec![phrase_words("food"), phrase_words("food poisoning")];
+        let tokens = tokenize("I got food poisoning, the food was off");
+        let found = match_phrases(&tokens, &phrases, false);
+        assert_eq!(found, vec![
+            PhraseMatch { phrase: 1, start: 2, len: 2 },
+            PhraseMatch { phrase: 0, start: 5, len: 1 },
+        ]);
+    }
+
+    #[test]
+    fn test_match_phrases_spans_hyphens() {
+        let phrases = vec![phrase_words("top-notch")];
+        assert_eq!(match_phrases(&tokenize("Top notch service"), &phrases, false).len(), 1);
+        assert_eq!(match_phrases(&tokenize("top-notch!"), &phrases, false).len(), 1);
+    }
+
+    #[test]
+    fn test_match_phrases_stems() {
+        let phrases = vec![phrase_words("iyi"), phrase_words("uygun"), phrase_words("uygunsuz")];
+        let tokens = tokenize("Yemekler iyiydi ama fiyat uygunsuzdu");
+        assert!(match_phrases(&tokens, &phrases, false).is_empty());
+        let found = match_phrases(&tokens, &phrases, true);
+        assert_eq!(found.iter().map(|m| m.phrase).collect::<Vec<_>>(), [0, 2]);
+    }
+
+    #[test]
+    fn test_tokenize_splits_french_elisions() {
+        let text = "L'accueil n'était pas chaleureux, c\u{2019}est dommage. Don't panic";
+        assert_eq!(words(text), ["accueil", "était", "pas", "chaleureux", "est", "dommage", "don't", "panic"]);
+        let tokens = tokenize(text);
+        assert_eq!(&text[tokens[0].start..tokens[0].end], "accueil");
+    }
+
+    #[test]
+    fn test_tokenize_shortens_elongations() {
+        let tokens = tokenize("Sooo goood, I LOVED it!!! ok!");
+        assert_eq!(tokens.iter().map(|t| t.text.as_str()).collect::<Vec<_>>(), ["soo", "good", "i", "loved", "it", "ok"]);
+        assert_eq!(tokens.iter().map(|t| t.elongated).collect::<Vec<_>>(), [true, true, false, false, false, false]);
+        assert_eq!(tokens.iter().map(|t| t.shouted).collect::<Vec<_>>(), [false, false, false, true, false, false]);
+        assert_eq!(tokens.iter().map(|t| t.exclamations).collect::<Vec<_>>(), [0, 0, 0, 0, 3, 1]);
+        assert_eq!(collapse_runs("amaaazing", 1), "amazing");
+        assert_eq!(collapse_runs("1000", 1), "1000");
+    }
+
+    #[test]
+    fn test_sentences() {
+        let text = "Great food!!! But the waiter was rude.  Never again\n\nok";
+        let found: Vec<&str> = sentences(text).into_iter().map(|r| &text[r]).collect();
+        assert_eq!(found, ["Great food!!!", "But the waiter was rude.", "Never again", "ok"]);
+        assert!(sentences("  ").is_empty());
+    }
+
+    #[test]
+    fn test_tokenize_normalizes_turkish_dotted_i() {
+        assert_eq!(words("İYİ"), ["iyi"]);
+    }
+}
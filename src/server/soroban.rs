@@ -1,60 +1,60 @@
 use std::process::Command;
 use log::{info, error};
+use crate::model::{onchain_excerpt, CommentError};
+use super::redaction;
 
 /// Submit a negative comment to the Soroban smart contract.
 /// This runs in a background blocking task to avoid blocking the Actix worker thread.
+/// `content` is redacted again here, so personal data never reaches the
+/// contract even if a caller passes raw text.
 pub fn submit_negative_comment(comment_id: String, score: u32, content: String) {
     // Spawn a blocking task for the CLI command
     // We don't await the result (fire-and-forget)
     tokio::task::spawn_blocking(move || {
-        let contract_id = std::env::var("CONTRACT_ID").unwrap_or_default();
-        if contract_id.is_empty() {
-            error!("CONTRACT_ID not set, skipping Soroban submission");
-            return;
-        }
-
         // Simple content "hash" (truncation) for demo purposes
         // In a real app, use SHA256
-        let content_hash = if content.len() > 32 {
-            content[..32].to_string() + "..."
-        } else {
-            content
-        };
-
-        info!("Submitting negative comment to Soroban: {} (score={}) - \"{}\"", comment_id, score, content_hash);
+        let content_hash = onchain_excerpt(&redaction::redact(&content).text);
 
-        // stellar contract invoke --id ... --network testnet --source deployer -- submit_negative ...
-        let output = Command::new("stellar")
-            .arg("contract")
-            .arg("invoke")
-            .arg("--id")
-            .arg(&contract_id)
-            .arg("--network")
-            .arg("testnet")
-            .arg("--source")
-            .arg("deployer")
-            .arg("--")
-            .arg("submit_negative")
-            .arg("--comment_id")
-            .arg(&comment_id)
-            .arg("--score")
-            .arg(score.to_string())
-            .arg("--content_hash")
-            .arg(&content_hash)
-            .output();
-
-        match output {
-            Ok(o) => {
-                if o.status.success() {
-                    info!("✅ Soroban submission successful for {}", comment_id);
-                } else {
-                    let stderr = String::from_utf8_lossy(&o.stderr);
-                    error!("❌ Soroban submission failed: {}", stderr);
-                }
-            }
-            Err(e) => {
-                error!("❌ Failed to execute stellar CLI: {}", e);
-            }
+        match invoke_submit_negative(&comment_id, score, &content_hash) {
+            Ok(()) => info!("✅ Soroban submission successful for {}", comment_id),
+            Err(e) => error!("❌ Soroban submission of {} failed: {}", comment_id, e.message()),
         }
     });
 }
+
+/// Run `stellar contract invoke ... submit_negative` and wait for it.
+fn invoke_submit_negative(comment_id: &str, score: u32, content_hash: &str) -> Result<(), CommentError> {
+    let contract_id = std::env::var("CONTRACT_ID").unwrap_or_default();
+    if contract_id.is_empty() {
+        return Err(CommentError::ChainSubmission("CONTRACT_ID not set, skipping Soroban submission".to_string()));
+    }
+
+    info!("Submitting negative comment to Soroban: {} (score={}) - \"{}\"", comment_id, score, content_hash);
+
+    // stellar contract invoke --id ... --network testnet --source deployer -- submit_negative ...
+    let output = Command::new("stellar")
+        .arg("contract")
+        .arg("invoke")
+        .arg("--id")
+        .arg(&contract_id)
+        .arg("--network")
+        .arg("testnet")
+        .arg("--source")
+        .arg("deployer")
+        .arg("--")
+        .arg("submit_negative")
+        .arg("--comment_id")
+        .arg(comment_id)
+        .arg("--score")
+        .arg(score.to_string())
+        .arg("--content_hash")
+        .arg(content_hash)
+        .output()
+        .map_err(|e| CommentError::ChainSubmission(format!("Failed to execute stellar CLI: {}", e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(CommentError::ChainSubmission(stderr.trim().to_string()));
+    }
+    Ok(())
+}
@@ -0,0 +1,228 @@
+//! Spam checks run before a comment is stored.
+//!
+//! Three signals: near-duplicates of recent comments on the same post
+//! (MinHash over word shingles), links and phone numbers, and authors
+//! posting in bursts. Clear cases are rejected; everything else is stored
+//! with its spam score.
+
+use std::collections::HashSet;
+use crate::model::{Comment, CommentError};
+use super::redaction;
+use super::sentiment::tokenizer::tokenize;
+
+/// Words per shingle.
+const SHINGLE_WORDS: usize = 3;
+/// Hash functions per MinHash signature.
+const SIGNATURE_SIZE: usize = 64;
+/// Estimated Jaccard similarity at which a comment counts as a duplicate.
+pub const DUPLICATE_SIMILARITY: f64 = 0.8;
+/// Below this many words, only the author's own comments count as
+/// duplicates: two guests may both write "Great food!".
+const MIN_DUPLICATE_WORDS: usize = 6;
+/// How far back, and how many comments, duplicates are looked for.
+pub const RECENT_WINDOW_SECS: i64 = 7 * 24 * 3600;
+pub const RECENT_LIMIT: i64 = 200;
+/// More than `BURST_LIMIT` comments by one author within
+/// `BURST_WINDOW_SECS` is a burst.
+pub const BURST_WINDOW_SECS: i64 = 10 * 60;
+pub const BURST_LIMIT: usize = 5;
+/// Score from links, phone numbers and the rest at which a comment is
+/// rejected.
+const REJECT_SCORE: u8 = 70;
+
+const LINK_SCORE: u32 = 35;
+const PHONE_SCORE: u32 = 40;
+/// Per comment the author already posted in the burst window.
+const BURST_SCORE: u32 = 10;
+/// Scale for similarities of at least `NEAR_SIMILARITY` below the
+/// duplicate threshold.
+const NEAR_DUPLICATE_SCORE: f64 = 50.0;
+const NEAR_SIMILARITY: f64 = 0.5;
+
+/// Top-level domains that make a bare "word.tld" a link.
+const LINK_TLDS: &[&str] = &[
+    "com", "net", "org", "io", "co", "biz", "info", "xyz", "shop", "online", "site", "ru", "tr", "fr", "de", "uk",
+];
+
+/// Outcome of checking a new comment.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SpamCheck {
+    /// 0–100; stored on accepted comments.
+    pub score: u8,
+    /// Why the comment may not be posted, if it may not.
+    pub rejection: Option<CommentError>,
+}
+
+/// MinHash signature of a text's word shingles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    mins: Vec<u64>,
+    words: usize,
+}
+
+impl Signature {
+    pub fn new(text: &str) -> Self {
+        let words: Vec<String> = tokenize(text).into_iter().map(|t| t.text).collect();
+        let shingles: HashSet<String> = if words.len() < SHINGLE_WORDS {
+            HashSet::from([words.join(" ")])
+        } else {
+            words.windows(SHINGLE_WORDS).map(|w| w.join(" ")).collect()
+        };
+
+        let bases: Vec<u64> = shingles.iter().map(|s| fnv1a(s.as_bytes())).collect();
+        let mins = (0..SIGNATURE_SIZE as u64)
+            .map(|seed| bases.iter().map(|b| splitmix64(b ^ seed.wrapping_mul(0x9E37_79B9_7F4A_7C15))).min().unwrap_or(0))
+            .collect();
+        Signature { mins, words: words.len() }
+    }
+
+    /// Estimated Jaccard similarity of the two shingle sets.
+    pub fn similarity(&self, other: &Signature) -> f64 {
+        let equal = self.mins.iter().zip(&other.mins).filter(|(a, b)| a == b).count();
+        equal as f64 / SIGNATURE_SIZE as f64
+    }
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, b| (hash ^ *b as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// URLs and bare domains ("cheap-eats.xyz").
+fn count_links(text: &str) -> usize {
+    text.split_whitespace()
+        .filter(|word| {
+            let word = word.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase();
+            if word.contains("http://") || word.contains("https://") || word.starts_with("www.") {
+                return true;
+            }
+            let mut labels = word.split('.');
+            let first = labels.next().unwrap_or_default();
+            let last = word.rsplit('.').next().unwrap_or_default();
+            word.contains('.') && !first.is_empty() && LINK_TLDS.contains(&last)
+        })
+        .count()
+}
+
+/// Phone numbers ("+90 555 123 45 67"), as redaction finds them.
+fn count_phone_numbers(text: &str) -> usize {
+    redaction::find_phone_numbers(text).len()
+}
+
+/// Check `content` by `author` against the recent comments on the same
+/// post and the number of comments the author posted within the burst
+/// window.
+pub fn check(content: &str, author: &str, recent: &[Comment], recent_by_author: usize) -> SpamCheck {
+    if recent_by_author >= BURST_LIMIT {
+        return SpamCheck {
+            score: 100,
+            rejection: Some(CommentError::RateLimited(format!(
+                "Too many reviews in a short time: please wait before posting again (limit {} per {} minutes)",
+                BURST_LIMIT,
+                BURST_WINDOW_SECS / 60
+            ))),
+        };
+    }
+
+    // Stored comments are redacted, so compare the new text in that form.
+    let signature = Signature::new(&redaction::redact(content).text);
+    let mut closest = 0.0_f64;
+    for comment in recent {
+        let own = comment.author_public_key == author;
+        if !own && signature.words < MIN_DUPLICATE_WORDS {
+            continue;
+        }
+        let similarity = signature.similarity(&Signature::new(&redaction::redact(&comment.content).text));
+        if similarity >= DUPLICATE_SIMILARITY {
+            let whose = if own { "one of your recent reviews" } else { "a recent review" };
+            return SpamCheck {
+                score: 100,
+                rejection: Some(CommentError::Validation(format!(
+                    "This review is a near-duplicate of {} on this post",
+                    whose
+                ))),
+            };
+        }
+        closest = closest.max(similarity);
+    }
+
+    let mut score = count_links(content) as u32 * LINK_SCORE
+        + count_phone_numbers(content) as u32 * PHONE_SCORE
+        + recent_by_author as u32 * BURST_SCORE;
+    if closest >= NEAR_SIMILARITY {
+        score += (closest * NEAR_DUPLICATE_SCORE).round() as u32;
+    }
+    let score = score.min(100) as u8;
+
+    SpamCheck {
+        score,
+        rejection: (score >= REJECT_SCORE)
+            .then(|| CommentError::Validation(
+                "This review looks like advertising: remove the links or phone numbers and try again".to_string(),
+            )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comment(author: &str, content: &str) -> Comment {
+        Comment { author_public_key: author.to_string(), ..Comment::sample(content) }
+    }
+
+    #[test]
+    fn test_signature_similarity() {
+        let a = Signature::new("The lamb was tender and the wine list is excellent, we will be back");
+        let b = Signature::new("the lamb was tender and the wine list is EXCELLENT!! We will be back.");
+        let c = Signature::new("Slow service tonight, the soup arrived cold and nobody apologised");
+        assert_eq!(a.similarity(&b), 1.0);
+        assert!(a.similarity(&c) < 0.2);
+    }
+
+    #[test]
+    fn test_rejects_near_duplicates() {
+        let text = "The lamb was tender and the wine list is excellent, we will be back";
+        let recent = [comment("G1", text)];
+        assert!(check(&format!("{}!", text), "G1", &recent, 1).rejection.is_some());
+        assert!(check(&format!("{}!", text), "G2", &recent, 0).rejection.is_some());
+
+        // short texts are only duplicates when the same author repeats them
+        let recent = [comment("G1", "Great food!")];
+        assert!(check("great food", "G2", &recent, 0).rejection.is_none());
+        assert!(check("great food", "G1", &recent, 1).rejection.is_some());
+
+        // a repost with an email matches the redacted copy that was stored
+        let text = "The lamb was tender, write to me at ana@example.com for the wine list";
+        let recent = [comment("G1", &redaction::redact(text).text)];
+        assert!(check(text, "G2", &recent, 0).rejection.is_some());
+    }
+
+    #[test]
+    fn test_links_and_phone_numbers() {
+        assert_eq!(count_links("Visit https://x.io or cheap-eats.xyz, good.The end"), 2);
+        assert_eq!(count_phone_numbers("Call +90 (555) 123-45-67 now, table for 4 at 19.30"), 1);
+        // Whatever redaction masks as a phone number counts here too
+        let text = "Ring 0555/123 45 67 or +44 20 7946 0958";
+        let masked = redaction::redact(text).found.iter().filter(|p| **p == redaction::Pii::Phone).count();
+        assert_eq!((count_phone_numbers(text), masked), (2, 2));
+        assert_eq!(count_phone_numbers("Booked for 12.10.2026 19.30"), 0);
+
+        let one_link = check("Nice place, menu at www.example.com", "G", &[], 0);
+        assert_eq!((one_link.score, one_link.rejection.is_none()), (35, true));
+        let ad = check("Cheap deals at www.example.com, call 0555 123 45 67", "G", &[], 0);
+        assert!(ad.rejection.is_some());
+    }
+
+    #[test]
+    fn test_rejects_bursts() {
+        assert!(check("Lovely", "G", &[], BURST_LIMIT - 1).rejection.is_none());
+        assert!(matches!(check("Lovely", "G", &[], BURST_LIMIT).rejection, Some(CommentError::RateLimited(_))));
+    }
+}
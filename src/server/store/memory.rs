@@ -0,0 +1,220 @@
+//! Comments kept in process memory, for development and tests. Sealed
+//! originals are not kept: with nothing surviving a restart there is no
+//! one to recover them for.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use async_trait::async_trait;
+use bson::oid::ObjectId;
+use crate::model::{AspectSummary, Comment, CommentError, CommentSort, LabelOverride, ModerationStatus, TopKeyphrases, Topic};
+use super::{
+    not_found, parse_id, tally_aspects, tally_keyphrases, AnalysisFields, CommentStore, ContentEdit, Listing, PageCursor,
+};
+
+/// Comments in a map keyed by id; nothing survives a restart.
+#[derive(Default)]
+pub struct MemoryStore {
+    /// By id, so iteration is in id order.
+    comments: Mutex<BTreeMap<String, Comment>>,
+    /// By post, largest first.
+    topics: Mutex<HashMap<String, Vec<Topic>>>,
+}
+
+fn is_visible(comment: &Comment) -> bool {
+    !comment.is_deleted && comment.moderation != ModerationStatus::Hidden
+}
+
+fn newest_first(a: &Comment, b: &Comment) -> Ordering {
+    b.created_at.cmp(&a.created_at)
+}
+
+impl MemoryStore {
+    fn comments(&self) -> Result<std::sync::MutexGuard<'_, BTreeMap<String, Comment>>, CommentError> {
+        self.comments.lock().map_err(|_| CommentError::Storage("Memory store poisoned".to_string()))
+    }
+
+    fn topics(&self) -> Result<std::sync::MutexGuard<'_, HashMap<String, Vec<Topic>>>, CommentError> {
+        self.topics.lock().map_err(|_| CommentError::Storage("Memory store poisoned".to_string()))
+    }
+
+    /// Comments matching `filter`, in `order`.
+    fn select(
+        &self,
+        filter: impl Fn(&Comment) -> bool,
+        order: impl FnMut(&Comment, &Comment) -> Ordering,
+    ) -> Result<Vec<Comment>, CommentError> {
+        let mut comments: Vec<Comment> =
+            self.comments()?.values().filter(|c| filter(c)).cloned().collect();
+        comments.sort_by(order);
+        Ok(comments)
+    }
+
+    /// Change the comment with `id` in place.
+    fn modify(&self, id: &str, change: impl FnOnce(&mut Comment)) -> Result<(), CommentError> {
+        parse_id(id)?;
+        let mut comments = self.comments()?;
+        let comment = comments.get_mut(id).ok_or_else(not_found)?;
+        change(comment);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CommentStore for MemoryStore {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    async fn create(&self, mut comment: Comment, _content_original: Option<String>) -> Result<Comment, CommentError> {
+        let id = ObjectId::new().to_hex();
+        comment.id = Some(id.clone());
+        self.comments()?.insert(id, comment.clone());
+        Ok(comment)
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<Comment>, CommentError> {
+        parse_id(id)?;
+        Ok(self.comments()?.get(id).cloned())
+    }
+
+    async fn list_by_post(&self, post_id: &str, since: Option<i64>) -> Result<Vec<Comment>, CommentError> {
+        self.select(
+            |c| c.post_id == post_id && is_visible(c) && since.is_none_or(|s| c.created_at >= s),
+            |a, b| a.created_at.cmp(&b.created_at),
+        )
+    }
+
+    async fn list_recent(&self, post_id: &str, since: i64, limit: usize) -> Result<Vec<Comment>, CommentError> {
+        let mut comments =
+            self.select(|c| c.post_id == post_id && !c.is_deleted && c.created_at >= since, newest_first)?;
+        comments.truncate(limit);
+        Ok(comments)
+    }
+
+    async fn count_by_author_since(&self, author_public_key: &str, since: i64) -> Result<usize, CommentError> {
+        Ok(self
+            .comments()?
+            .values()
+            .filter(|c| c.author_public_key == author_public_key && c.created_at >= since)
+            .count())
+    }
+
+    async fn update(&self, id: &str, edit: &ContentEdit) -> Result<(), CommentError> {
+        self.modify(id, |comment| edit.apply(comment))
+    }
+
+    async fn set_analysis(&self, id: &str, analysis: &AnalysisFields) -> Result<(), CommentError> {
+        self.modify(id, |comment| analysis.apply(comment))
+    }
+
+    async fn like(&self, id: &str) -> Result<(), CommentError> {
+        let now = bson::DateTime::now().timestamp_millis();
+        self.modify(id, |comment| {
+            comment.likes_count += 1;
+            comment.updated_at = now;
+        })
+    }
+
+    async fn soft_delete(&self, id: &str) -> Result<(), CommentError> {
+        let now = bson::DateTime::now().timestamp_millis();
+        self.modify(id, |comment| {
+            comment.is_deleted = true;
+            comment.updated_at = now;
+        })
+    }
+
+    async fn set_moderation(&self, id: &str, moderation: ModerationStatus) -> Result<(), CommentError> {
+        self.modify(id, |comment| comment.moderation = moderation)
+    }
+
+    async fn set_human_label(&self, id: &str, change: &LabelOverride) -> Result<(), CommentError> {
+        self.modify(id, |comment| {
+            comment.status = change.new_status;
+            comment.human_status = Some(change.new_status);
+            comment.needs_review = false;
+            comment.label_overrides.push(change.clone());
+        })
+    }
+
+    async fn list_negative(&self, post_id: &str) -> Result<Vec<Comment>, CommentError> {
+        self.select(|c| c.post_id == post_id && c.status == 1 && is_visible(c), newest_first)
+    }
+
+    async fn list_replies(&self, parent_ids: &[String]) -> Result<Vec<Comment>, CommentError> {
+        self.select(
+            |c| is_visible(c) && c.parent_id.as_ref().is_some_and(|p| parent_ids.contains(p)),
+            |a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)),
+        )
+    }
+
+    async fn list_page(
+        &self,
+        listing: &Listing,
+        sort: CommentSort,
+        after: Option<&PageCursor>,
+        limit: usize,
+    ) -> Result<Vec<Comment>, CommentError> {
+        let mut comments = self.select(
+            |c| listing.contains(c) && after.is_none_or(|a| a.precedes(sort, c)),
+            |a, b| {
+                let (a_id, b_id) = (a.id.as_deref().unwrap_or_default(), b.id.as_deref().unwrap_or_default());
+                sort.compare((sort.key(a), a_id), (sort.key(b), b_id))
+            },
+        )?;
+        comments.truncate(limit);
+        Ok(comments)
+    }
+
+    async fn list_after(&self, after: Option<&str>, limit: usize) -> Result<Vec<Comment>, CommentError> {
+        if let Some(after) = after {
+            parse_id(after)?;
+        }
+        Ok(self
+            .comments()?
+            .iter()
+            .filter(|(id, _)| after.is_none_or(|after| id.as_str() > after))
+            .take(limit)
+            .map(|(_, c)| c.clone())
+            .collect())
+    }
+
+    async fn moderation_queue(&self) -> Result<Vec<Comment>, CommentError> {
+        self.select(
+            |c| !c.is_deleted && c.moderation != ModerationStatus::Visible,
+            |a, b| b.toxicity.cmp(&a.toxicity).then_with(|| newest_first(a, b)),
+        )
+    }
+
+    async fn review_queue(&self) -> Result<Vec<Comment>, CommentError> {
+        self.select(
+            |c| !c.is_deleted && c.needs_review,
+            |a, b| a.confidence.total_cmp(&b.confidence).then_with(|| newest_first(a, b)),
+        )
+    }
+
+    async fn list_human_labelled(&self) -> Result<Vec<Comment>, CommentError> {
+        self.select(|c| !c.is_deleted && c.human_status.is_some(), |a, b| a.created_at.cmp(&b.created_at))
+    }
+
+    async fn aspect_summary(&self, post_id: &str) -> Result<Vec<AspectSummary>, CommentError> {
+        let comments = self.comments()?;
+        Ok(tally_aspects(comments.values().filter(|c| c.post_id == post_id && is_visible(c))))
+    }
+
+    async fn top_keyphrases(&self, post_id: &str, since: Option<i64>, limit: usize) -> Result<TopKeyphrases, CommentError> {
+        let comments = self.list_by_post(post_id, since).await?;
+        Ok(tally_keyphrases(&comments, limit))
+    }
+
+    async fn replace_topics(&self, post_id: &str, topics: &[Topic]) -> Result<(), CommentError> {
+        let mut topics = topics.to_vec();
+        topics.sort_by_key(|t| std::cmp::Reverse(t.size));
+        self.topics()?.insert(post_id.to_string(), topics);
+        Ok(())
+    }
+
+    async fn list_topics(&self, post_id: &str) -> Result<Vec<Topic>, CommentError> {
+        Ok(self.topics()?.get(post_id).cloned().unwrap_or_default())
+    }
+}
@@ -0,0 +1,572 @@
+//! Pluggable comment storage.
+//!
+//! `comment_crud` holds the rules — spam checks, redaction, analysis — and
+//! reads and writes comments through a [`CommentStore`], which also keeps
+//! the complaint topics clustered from them. Which backend sits
+//! behind it is chosen once at startup via the `COMMENT_STORE` env var:
+//! `mongodb` (the default), `sqlite` (an embedded database file at
+//! `SQLITE_PATH`) or `memory` (lost on exit; for development and tests).
+
+pub mod memory;
+pub mod mongo;
+pub mod sqlite;
+
+pub use memory::MemoryStore;
+pub use mongo::MongoStore;
+pub use sqlite::SqliteStore;
+
+use std::collections::{BTreeMap, HashMap};
+use async_trait::async_trait;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64;
+use base64::Engine;
+use bson::oid::ObjectId;
+use once_cell::sync::OnceCell;
+use crate::model::{
+    AspectSentiment, AspectSummary, Comment, CommentError, CommentSort, Keyphrase, LabelOverride, ModerationStatus, PhraseCount,
+    SentenceSentiment, SentimentTerm, ToxicTerm, TopKeyphrases, Topic,
+};
+use super::review;
+use super::sentiment::Analysis;
+use super::toxicity;
+
+/// Default `SQLITE_PATH`.
+pub const DEFAULT_SQLITE_PATH: &str = "data/dene.sqlite3";
+
+/// The sentiment fields of a comment, as written after (re-)analysis.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalysisFields {
+    /// `human_status` when set, else `auto_status`.
+    pub status: u8,
+    pub auto_status: u8,
+    /// `None` drops an earlier human label.
+    pub human_status: Option<u8>,
+    pub confidence: f32,
+    pub needs_review: bool,
+    pub scoring: u8,
+    pub unanalyzed: bool,
+    pub language: Option<String>,
+    pub analyzer_version: Option<String>,
+    pub aspects: Vec<AspectSentiment>,
+    pub evidence: Vec<SentimentTerm>,
+    pub sentences: Vec<SentenceSentiment>,
+    pub keyphrases: Vec<Keyphrase>,
+}
+
+impl AnalysisFields {
+    /// Fields for `analysis`. A moderator's `human_status` stays the
+    /// effective `status`, and the comment is not sent for review again.
+    pub fn new(analysis: &Analysis, human_status: Option<u8>) -> Self {
+        AnalysisFields {
+            status: human_status.unwrap_or(analysis.result.status()),
+            auto_status: analysis.result.status(),
+            human_status,
+            confidence: analysis.result.confidence,
+            needs_review: human_status.is_none() && review::needs_review(analysis),
+            scoring: analysis.result.score,
+            unanalyzed: !analysis.is_analyzed(),
+            language: analysis.language.map(|l| l.code().to_string()),
+            analyzer_version: analysis.analyzer_version.clone(),
+            aspects: analysis.result.aspects(),
+            evidence: analysis
+                .result
+                .evidence
+                .iter()
+                .map(|e| SentimentTerm { term: e.term.clone(), weight: e.weight, start: e.start, end: e.end })
+                .collect(),
+            sentences: analysis
+                .sentences
+                .iter()
+                .map(|s| SentenceSentiment {
+                    start: s.start,
+                    end: s.end,
+                    status: s.result.status(),
+                    scoring: s.result.score,
+                })
+                .collect(),
+            keyphrases: analysis.keyphrases.clone(),
+        }
+    }
+
+    /// The fields as stored on `comment`.
+    pub fn of(comment: &Comment) -> Self {
+        AnalysisFields {
+            status: comment.status,
+            auto_status: comment.auto_status,
+            human_status: comment.human_status,
+            confidence: comment.confidence,
+            needs_review: comment.needs_review,
+            scoring: comment.scoring,
+            unanalyzed: comment.unanalyzed,
+            language: comment.language.clone(),
+            analyzer_version: comment.analyzer_version.clone(),
+            aspects: comment.aspects.clone(),
+            evidence: comment.evidence.clone(),
+            sentences: comment.sentences.clone(),
+            keyphrases: comment.keyphrases.clone(),
+        }
+    }
+
+    pub fn apply(&self, comment: &mut Comment) {
+        comment.status = self.status;
+        comment.auto_status = self.auto_status;
+        comment.human_status = self.human_status;
+        comment.confidence = self.confidence;
+        comment.needs_review = self.needs_review;
+        comment.scoring = self.scoring;
+        comment.unanalyzed = self.unanalyzed;
+        comment.language = self.language.clone();
+        comment.analyzer_version = self.analyzer_version.clone();
+        comment.aspects = self.aspects.clone();
+        comment.evidence = self.evidence.clone();
+        comment.sentences = self.sentences.clone();
+        comment.keyphrases = self.keyphrases.clone();
+    }
+}
+
+/// Toxicity fields and the moderation status they lead to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToxicityFields {
+    pub toxicity: u8,
+    pub toxic_terms: Vec<ToxicTerm>,
+    pub moderation: ModerationStatus,
+}
+
+impl ToxicityFields {
+    /// Classify new or edited content.
+    pub fn new(content: &str) -> Self {
+        let (toxicity, moderation) = toxicity::assess(content);
+        ToxicityFields { toxicity: toxicity.score, toxic_terms: toxicity.terms, moderation }
+    }
+
+    pub fn apply(&self, comment: &mut Comment) {
+        comment.toxicity = self.toxicity;
+        comment.toxic_terms = self.toxic_terms.clone();
+        comment.moderation = self.moderation;
+    }
+}
+
+/// New text of an edited comment, with everything derived from it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContentEdit {
+    /// Redacted text.
+    pub content: String,
+    pub redacted: bool,
+    /// Sealed original text; `None` removes a stored one.
+    pub content_original: Option<String>,
+    pub analysis: AnalysisFields,
+    pub toxicity: ToxicityFields,
+    pub spam_score: u8,
+    /// Milliseconds since epoch
+    pub updated_at: i64,
+}
+
+impl ContentEdit {
+    /// Apply the edit to `comment`; `content_original` is the store's to
+    /// keep. An edit can queue or hide a comment but never lowers its
+    /// moderation status: a hidden or queued comment stays so until a
+    /// moderator decides.
+    pub fn apply(&self, comment: &mut Comment) {
+        comment.content = self.content.clone();
+        comment.redacted = self.redacted;
+        comment.spam_score = self.spam_score;
+        comment.updated_at = self.updated_at;
+        self.analysis.apply(comment);
+        let moderation = comment.moderation;
+        self.toxicity.apply(comment);
+        comment.moderation = comment.moderation.max(moderation);
+    }
+}
+
+/// Which comments a listing is drawn from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Listing {
+    /// Non-deleted, non-hidden comments on a post, optionally only those
+    /// with one `status` and only those that are not replies.
+    Post { post_id: String, status: Option<u8>, top_level: bool },
+    /// Every comment, deleted ones included.
+    All,
+}
+
+impl Listing {
+    pub fn contains(&self, comment: &Comment) -> bool {
+        match self {
+            Listing::Post { post_id, status, top_level } => {
+                comment.post_id == *post_id
+                    && !comment.is_deleted
+                    && comment.moderation != ModerationStatus::Hidden
+                    && status.is_none_or(|s| comment.status == s)
+                    && !(*top_level && comment.parent_id.is_some())
+            }
+            Listing::All => true,
+        }
+    }
+}
+
+/// Position in a listing: the sort key and id of the last comment of a
+/// page. Sent to clients as an opaque token that is only valid for the
+/// sort it was made for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageCursor {
+    pub key: i64,
+    pub id: String,
+}
+
+impl PageCursor {
+    /// Position just after `comment`.
+    pub fn after(sort: CommentSort, comment: &Comment) -> Self {
+        PageCursor { key: sort.key(comment), id: comment.id.clone().unwrap_or_default() }
+    }
+
+    pub fn encode(&self, sort: CommentSort) -> String {
+        BASE64.encode(format!("{}:{}:{}", sort.as_str(), self.key, self.id))
+    }
+
+    pub fn decode(token: &str, sort: CommentSort) -> Result<Self, CommentError> {
+        let invalid = || CommentError::Validation("Invalid page cursor".to_string());
+        let bytes = BASE64.decode(token.trim()).map_err(|_| invalid())?;
+        let text = String::from_utf8(bytes).map_err(|_| invalid())?;
+        let mut parts = text.splitn(3, ':');
+        let (Some(name), Some(key), Some(id)) = (parts.next(), parts.next(), parts.next()) else {
+            return Err(invalid());
+        };
+        if name != sort.as_str() {
+            return Err(CommentError::Validation(format!("Page cursor is for sort {}, not {}", name, sort.as_str())));
+        }
+        let key = key.parse().map_err(|_| invalid())?;
+        parse_id(id)?;
+        Ok(PageCursor { key, id: id.to_string() })
+    }
+
+    /// Whether `comment` comes after this position in `sort` order.
+    pub fn precedes(&self, sort: CommentSort, comment: &Comment) -> bool {
+        let id = comment.id.as_deref().unwrap_or_default();
+        sort.compare((self.key, &self.id), (sort.key(comment), id)).is_lt()
+    }
+}
+
+/// Where comments live. Ids are ObjectId hex strings in every backend.
+/// Updates of an id that is not stored fail with `CommentError::NotFound`.
+#[async_trait]
+pub trait CommentStore: Send + Sync {
+    /// Backend name, for logs.
+    fn name(&self) -> &'static str;
+
+    /// Store a new comment and return it with its id. `content_original`
+    /// is the sealed original text: persistent backends keep it, but never
+    /// read it back.
+    async fn create(&self, comment: Comment, content_original: Option<String>) -> Result<Comment, CommentError>;
+
+    async fn get(&self, id: &str) -> Result<Option<Comment>, CommentError>;
+
+    /// Non-deleted, non-hidden comments on a post, created at or after
+    /// `since` (milliseconds since epoch) if given, oldest first.
+    async fn list_by_post(&self, post_id: &str, since: Option<i64>) -> Result<Vec<Comment>, CommentError>;
+
+    /// Up to `limit` non-deleted comments on a post created at or after
+    /// `since`, newest first.
+    async fn list_recent(&self, post_id: &str, since: i64, limit: usize) -> Result<Vec<Comment>, CommentError>;
+
+    /// Comments (on any post, deleted or not) an author created at or
+    /// after `since`.
+    async fn count_by_author_since(&self, author_public_key: &str, since: i64) -> Result<usize, CommentError>;
+
+    /// Replace a comment's content and everything derived from it, as
+    /// `ContentEdit::apply` does.
+    async fn update(&self, id: &str, edit: &ContentEdit) -> Result<(), CommentError>;
+
+    /// Replace a comment's analysis without touching its content or
+    /// `updated_at`.
+    async fn set_analysis(&self, id: &str, analysis: &AnalysisFields) -> Result<(), CommentError>;
+
+    /// Add one like.
+    async fn like(&self, id: &str) -> Result<(), CommentError>;
+
+    async fn soft_delete(&self, id: &str) -> Result<(), CommentError>;
+
+    async fn set_moderation(&self, id: &str, moderation: ModerationStatus) -> Result<(), CommentError>;
+
+    /// Make `change.new_status` the comment's human label and `status`,
+    /// take it out of review and append `change` to its `label_overrides`.
+    async fn set_human_label(&self, id: &str, change: &LabelOverride) -> Result<(), CommentError>;
+
+    /// Non-deleted, non-hidden negative comments on a post, newest first.
+    async fn list_negative(&self, post_id: &str) -> Result<Vec<Comment>, CommentError>;
+
+    /// Non-deleted, non-hidden replies to any of `parent_ids`, oldest
+    /// first.
+    async fn list_replies(&self, parent_ids: &[String]) -> Result<Vec<Comment>, CommentError>;
+
+    /// Up to `limit` comments of `listing` in `sort` order, starting after
+    /// `after` if given.
+    async fn list_page(
+        &self,
+        listing: &Listing,
+        sort: CommentSort,
+        after: Option<&PageCursor>,
+        limit: usize,
+    ) -> Result<Vec<Comment>, CommentError>;
+
+    /// Up to `limit` comments (deleted ones included) with an id after
+    /// `after`, in id order.
+    async fn list_after(&self, after: Option<&str>, limit: usize) -> Result<Vec<Comment>, CommentError>;
+
+    /// Non-deleted comments queued for moderation or hidden, most toxic
+    /// first.
+    async fn moderation_queue(&self) -> Result<Vec<Comment>, CommentError>;
+
+    /// Non-deleted comments flagged `needs_review`, least confident first.
+    async fn review_queue(&self) -> Result<Vec<Comment>, CommentError>;
+
+    /// Non-deleted comments with a human label, oldest first.
+    async fn list_human_labelled(&self) -> Result<Vec<Comment>, CommentError>;
+
+    /// Aspect counts over a post's non-deleted, non-hidden comments, in
+    /// aspect order.
+    async fn aspect_summary(&self, post_id: &str) -> Result<Vec<AspectSummary>, CommentError>;
+
+    /// The `limit` most frequent keyphrases per class of a post's
+    /// non-deleted, non-hidden comments created at or after `since` if
+    /// given. `days` is left for the caller.
+    async fn top_keyphrases(&self, post_id: &str, since: Option<i64>, limit: usize) -> Result<TopKeyphrases, CommentError>;
+
+    /// Replace a post's stored topics with `topics`.
+    async fn replace_topics(&self, post_id: &str, topics: &[Topic]) -> Result<(), CommentError>;
+
+    /// A post's stored topics, largest first.
+    async fn list_topics(&self, post_id: &str) -> Result<Vec<Topic>, CommentError>;
+}
+
+static STORE: OnceCell<Box<dyn CommentStore>> = OnceCell::new();
+
+/// Open the backend named by `COMMENT_STORE`. Call once at startup.
+pub async fn init_store() -> Result<(), String> {
+    let backend = std::env::var("COMMENT_STORE").unwrap_or_default();
+    let store: Box<dyn CommentStore> = match backend.trim() {
+        "" | "mongodb" => {
+            // Missing settings fail here; an unreachable cluster only with MONGODB_STARTUP=strict
+            super::db::init_db().await?;
+            Box::new(MongoStore)
+        }
+        "sqlite" => {
+            let path = std::env::var("SQLITE_PATH")
+                .ok()
+                .filter(|p| !p.trim().is_empty())
+                .unwrap_or_else(|| DEFAULT_SQLITE_PATH.to_string());
+            Box::new(SqliteStore::open(path.trim().as_ref())?)
+        }
+        "memory" => Box::new(MemoryStore::default()),
+        other => return Err(format!("Unknown COMMENT_STORE: {} (expected mongodb, sqlite or memory)", other)),
+    };
+    log::info!("Comments are stored in {}", store.name());
+
+    STORE
+        .set(store)
+        .map_err(|_| "Comment store already initialized".to_string())
+}
+
+/// The configured store, or an empty in-memory one if `init_store()` was
+/// never called.
+pub fn store() -> &'static dyn CommentStore {
+    STORE.get_or_init(|| Box::new(MemoryStore::default())).as_ref()
+}
+
+/// Check that `id` is an ObjectId hex string.
+pub fn parse_id(id: &str) -> Result<ObjectId, CommentError> {
+    ObjectId::parse_str(id).map_err(|e| CommentError::InvalidId(format!("Invalid id: {}", e)))
+}
+
+/// The error for an update of an id that is not stored.
+pub fn not_found() -> CommentError {
+    CommentError::NotFound("Comment not found".to_string())
+}
+
+/// A backend failure while doing `action`.
+pub fn storage_error(action: &str, e: impl std::fmt::Display) -> CommentError {
+    CommentError::Storage(format!("{} error: {}", action, e))
+}
+
+/// Negative/neutral/positive counts per aspect over `comments`, in aspect
+/// order. Aspects nobody mentioned are left out.
+pub fn tally_aspects<'a>(comments: impl IntoIterator<Item = &'a Comment>) -> Vec<AspectSummary> {
+    let mut counts: BTreeMap<_, [u32; 3]> = BTreeMap::new();
+    for aspect in comments.into_iter().flat_map(|c| &c.aspects) {
+        if (1..=3).contains(&aspect.status) {
+            counts.entry(aspect.aspect).or_default()[aspect.status as usize - 1] += 1;
+        }
+    }
+    counts
+        .into_iter()
+        .map(|(aspect, [negative, neutral, positive])| AspectSummary { aspect, negative, neutral, positive })
+        .collect()
+}
+
+/// The `limit` most frequent keyphrases per class over `comments`, most
+/// frequent first, ties alphabetically.
+pub fn tally_keyphrases<'a>(comments: impl IntoIterator<Item = &'a Comment>, limit: usize) -> TopKeyphrases {
+    let mut counts: [HashMap<&str, u32>; 3] = Default::default();
+    for keyphrase in comments.into_iter().flat_map(|c| &c.keyphrases) {
+        if (1..=3).contains(&keyphrase.status) {
+            *counts[keyphrase.status as usize - 1].entry(&keyphrase.phrase).or_default() += 1;
+        }
+    }
+    let [negative, neutral, positive] = counts.map(|class| {
+        let mut phrases: Vec<PhraseCount> =
+            class.into_iter().map(|(phrase, count)| PhraseCount { phrase: phrase.to_string(), count }).collect();
+        phrases.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.phrase.cmp(&b.phrase)));
+        phrases.truncate(limit);
+        phrases
+    });
+    TopKeyphrases { negative, neutral, positive, ..Default::default() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Aspect, TopicExample};
+
+    fn comment(post_id: &str, author: &str, status: u8, created_at: i64) -> Comment {
+        Comment {
+            post_id: post_id.to_string(),
+            author_public_key: author.to_string(),
+            status,
+            auto_status: status,
+            aspects: vec![AspectSentiment { aspect: Aspect::Food, status, weight: 1.0 }],
+            keyphrases: vec![Keyphrase { phrase: "cold soup".to_string(), status }],
+            created_at,
+            updated_at: created_at,
+            ..Comment::sample("The soup was cold")
+        }
+    }
+
+    fn topic(post_id: &str, label: &str, size: u32) -> Topic {
+        Topic {
+            post_id: post_id.to_string(),
+            label: label.to_string(),
+            terms: vec![label.to_string()],
+            size,
+            examples: vec![TopicExample { comment_id: ObjectId::new().to_hex(), content: "The soup was cold".to_string() }],
+            generated_at: 1_000,
+        }
+    }
+
+    /// The behaviour every backend shares.
+    async fn check_store(store: &dyn CommentStore) {
+        let a = store.create(comment("p", "G1", 1, 1_000), Some("sealed".to_string())).await.unwrap();
+        let b = store.create(comment("p", "G2", 3, 2_000), None).await.unwrap();
+        let c = store.create(comment("q", "G1", 1, 3_000), None).await.unwrap();
+        let (a_id, b_id, c_id) = (a.id.clone().unwrap(), b.id.clone().unwrap(), c.id.clone().unwrap());
+        assert!(parse_id(&a_id).is_ok());
+        assert_eq!(store.get(&a_id).await.unwrap().unwrap().content, "The soup was cold");
+        assert!(store.get(&ObjectId::new().to_hex()).await.unwrap().is_none());
+        assert!(matches!(store.get("nope").await, Err(CommentError::InvalidId(_))));
+
+        let ids = |comments: Vec<Comment>| comments.into_iter().map(|c| c.id.unwrap()).collect::<Vec<_>>();
+        assert_eq!(ids(store.list_by_post("p", None).await.unwrap()), [a_id.as_str(), b_id.as_str()]);
+        assert_eq!(ids(store.list_by_post("p", Some(1_500)).await.unwrap()), [b_id.as_str()]);
+        assert_eq!(ids(store.list_recent("p", 0, 1).await.unwrap()), [b_id.as_str()]);
+        assert_eq!(store.count_by_author_since("G1", 0).await.unwrap(), 2);
+        assert_eq!(store.count_by_author_since("G1", 2_000).await.unwrap(), 1);
+        assert_eq!(ids(store.list_negative("p").await.unwrap()), [a_id.as_str()]);
+        let page = |sort, after: Option<PageCursor>, limit| async move {
+            ids(store.list_page(&Listing::All, sort, after.as_ref(), limit).await.unwrap())
+        };
+        assert_eq!(page(CommentSort::Newest, None, 10).await, [c_id.as_str(), b_id.as_str(), a_id.as_str()]);
+        let after_c = PageCursor::after(CommentSort::Newest, &c);
+        assert_eq!(page(CommentSort::Newest, Some(after_c), 1).await, [b_id.as_str()]);
+        assert_eq!(ids(store.list_after(Some(&a_id), 10).await.unwrap()), [b_id.as_str(), c_id.as_str()]);
+        assert_eq!(ids(store.list_after(None, 1).await.unwrap()), [a_id.as_str()]);
+
+        store.like(&b_id).await.unwrap();
+        store.like(&b_id).await.unwrap();
+        assert_eq!(store.get(&b_id).await.unwrap().unwrap().likes_count, 2);
+        assert_eq!(page(CommentSort::MostLiked, None, 1).await, [b_id.as_str()]);
+        let liked = Listing::Post { post_id: "p".to_string(), status: Some(3), top_level: false };
+        assert_eq!(ids(store.list_page(&liked, CommentSort::MostLiked, None, 10).await.unwrap()), [b_id.as_str()]);
+
+        let mut edited = AnalysisFields::of(&b);
+        edited.status = 1;
+        edited.needs_review = true;
+        edited.confidence = 0.1;
+        let edit = ContentEdit {
+            content: "The bread was stale".to_string(),
+            redacted: false,
+            content_original: None,
+            analysis: edited.clone(),
+            toxicity: ToxicityFields { toxicity: 50, toxic_terms: Vec::new(), moderation: ModerationStatus::Queued },
+            spam_score: 35,
+            updated_at: 5_000,
+        };
+        store.update(&b_id, &edit).await.unwrap();
+        let b = store.get(&b_id).await.unwrap().unwrap();
+        assert_eq!((b.content.as_str(), b.status, b.updated_at, b.likes_count), ("The bread was stale", 1, 5_000, 2));
+        assert_eq!(b.spam_score, 35);
+        assert_eq!(ids(store.moderation_queue().await.unwrap()), [b_id.as_str()]);
+        assert_eq!(ids(store.review_queue().await.unwrap()), [b_id.as_str()]);
+
+        let change = LabelOverride { moderator: "ayse".to_string(), at: 6_000, old_status: 1, new_status: 3 };
+        store.set_human_label(&b_id, &change).await.unwrap();
+        let b = store.get(&b_id).await.unwrap().unwrap();
+        assert_eq!((b.status, b.human_status, b.needs_review), (3, Some(3), false));
+        assert_eq!(b.label_overrides, vec![change]);
+        assert!(store.review_queue().await.unwrap().is_empty());
+        assert_eq!(ids(store.list_human_labelled().await.unwrap()), [b_id.as_str()]);
+
+        edited.human_status = Some(3);
+        edited.status = 3;
+        edited.needs_review = false;
+        store.set_analysis(&b_id, &edited).await.unwrap();
+        assert_eq!(store.get(&b_id).await.unwrap().unwrap().updated_at, 5_000);
+
+        store.set_moderation(&b_id, ModerationStatus::Hidden).await.unwrap();
+        let clean = ContentEdit {
+            analysis: edited.clone(),
+            toxicity: ToxicityFields { toxicity: 0, toxic_terms: Vec::new(), moderation: ModerationStatus::Visible },
+            updated_at: 7_000,
+            ..edit.clone()
+        };
+        store.update(&b_id, &clean).await.unwrap();
+        let b = store.get(&b_id).await.unwrap().unwrap();
+        assert_eq!((b.updated_at, b.moderation), (7_000, ModerationStatus::Hidden));
+        assert_eq!(ids(store.list_by_post("p", None).await.unwrap()), [a_id.as_str()]);
+        // Only the visible `a` counts; the hidden `b` does not
+        let visible = store.list_by_post("p", None).await.unwrap();
+        assert_eq!(store.aspect_summary("p").await.unwrap(), tally_aspects(&visible));
+        let summary = store.aspect_summary("p").await.unwrap();
+        assert_eq!((summary.len(), summary[0].negative, summary[0].positive), (1, 1, 0));
+        let top = store.top_keyphrases("p", None, 5).await.unwrap();
+        assert_eq!((top.negative.len(), top.positive.len()), (1, 0));
+
+        store.soft_delete(&a_id).await.unwrap();
+        assert!(store.get(&a_id).await.unwrap().unwrap().is_deleted);
+        assert!(store.list_negative("p").await.unwrap().is_empty());
+        assert_eq!(store.list_page(&Listing::All, CommentSort::Oldest, None, 10).await.unwrap().len(), 3);
+        assert_eq!(store.like(&ObjectId::new().to_hex()).await, Err(not_found()));
+
+        let topics = [topic("p", "bread", 2), topic("p", "cold soup", 5)];
+        store.replace_topics("p", &topics).await.unwrap();
+        store.replace_topics("q", &[topic("q", "noise", 3)]).await.unwrap();
+        assert_eq!(store.list_topics("p").await.unwrap(), [topics[1].clone(), topics[0].clone()]);
+        store.replace_topics("p", &topics[..1]).await.unwrap();
+        assert_eq!(store.list_topics("p").await.unwrap(), [topics[0].clone()]);
+        assert_eq!(store.list_topics("q").await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_memory_store() {
+        check_store(&MemoryStore::default()).await;
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_store() {
+        check_store(&SqliteStore::open_in_memory().unwrap()).await;
+    }
+
+    #[test]
+    fn test_tally_keyphrases_ranks_per_class() {
+        let mut comments = vec![comment("p", "G", 1, 0), comment("p", "G", 1, 0), comment("p", "G", 1, 0)];
+        comments[2].keyphrases.push(Keyphrase { phrase: "bad bread".to_string(), status: 1 });
+        let top = tally_keyphrases(&comments, 1);
+        assert_eq!(top.negative, vec![PhraseCount { phrase: "cold soup".to_string(), count: 3 }]);
+        assert!(top.positive.is_empty());
+    }
+}
@@ -0,0 +1,660 @@
+//! Comments in the configured MongoDB collection (`comments` by default),
+//! topics in their own (`topics`).
+
+use async_trait::async_trait;
+use bson::{doc, Bson, Document};
+use futures::TryStreamExt;
+use mongodb::Collection;
+use crate::model::{
+    Aspect, AspectSentiment, AspectSummary, Comment, CommentError, CommentSort, Keyphrase, LabelOverride, ModerationStatus, PhraseCount,
+    SentenceSentiment, SentimentTerm, ToxicTerm, ToxicityCategory, TopKeyphrases, Topic, TopicExample,
+};
+use super::super::db::{get_comments_collection, get_topics_collection};
+use super::{
+    not_found, parse_id, storage_error, AnalysisFields, CommentStore, ContentEdit, Listing, PageCursor, ToxicityFields,
+};
+
+/// The MongoDB backend; the client is set up by `db::init_db()`.
+pub struct MongoStore;
+
+/// Convert a BSON Document to our Comment model.
+fn doc_to_comment(doc: &Document) -> Option<Comment> {
+    Some(Comment {
+        id: doc.get_object_id("_id").ok().map(|id| id.to_hex()),
+        post_id: doc.get_str("post_id").ok()?.to_string(),
+        author_public_key: doc.get_str("author_public_key").ok()?.to_string(),
+        content: doc.get_str("content").ok()?.to_string(),
+        redacted: doc.get_bool("redacted").ok().unwrap_or(false),
+        parent_id: doc.get_object_id("parent_id").ok().map(|id| id.to_hex()),
+        depth: doc.get_i32("depth").ok().unwrap_or(0) as u8,
+        status: doc.get_i32("status").ok().unwrap_or(2) as u8,
+        scoring: doc.get_i32("scoring").ok().unwrap_or(0) as u8,
+        auto_status: doc.get_i32("auto_status").or_else(|_| doc.get_i32("status")).ok().unwrap_or(2) as u8,
+        human_status: doc.get_i32("human_status").ok().map(|s| s as u8),
+        confidence: doc.get_f64("confidence").ok().unwrap_or(1.0) as f32,
+        needs_review: doc.get_bool("needs_review").ok().unwrap_or(false),
+        label_overrides: doc.get_array("label_overrides")
+            .map(|a| a.iter().filter_map(|b| b.as_document()).filter_map(doc_to_label_override).collect())
+            .unwrap_or_default(),
+        language: doc.get_str("language").ok().map(|l| l.to_string()),
+        unanalyzed: doc.get_bool("unanalyzed").ok().unwrap_or(false),
+        analyzer_version: doc.get_str("analyzer_version").ok().map(|v| v.to_string()),
+        aspects: doc.get_array("aspects")
+            .map(|a| a.iter().filter_map(|b| b.as_document()).filter_map(doc_to_aspect).collect())
+            .unwrap_or_default(),
+        evidence: doc.get_array("evidence")
+            .map(|a| a.iter().filter_map(|b| b.as_document()).filter_map(doc_to_term).collect())
+            .unwrap_or_default(),
+        sentences: doc.get_array("sentences")
+            .map(|a| a.iter().filter_map(|b| b.as_document()).filter_map(doc_to_sentence).collect())
+            .unwrap_or_default(),
+        keyphrases: doc.get_array("keyphrases")
+            .map(|a| a.iter().filter_map(|b| b.as_document()).filter_map(doc_to_keyphrase).collect())
+            .unwrap_or_default(),
+        toxicity: doc.get_i32("toxicity").ok().unwrap_or(0) as u8,
+        toxic_terms: doc.get_array("toxic_terms")
+            .map(|a| a.iter().filter_map(|b| b.as_document()).filter_map(doc_to_toxic_term).collect())
+            .unwrap_or_default(),
+        moderation: doc.get_str("moderation").ok().and_then(ModerationStatus::parse).unwrap_or_default(),
+        spam_score: doc.get_i32("spam_score").ok().unwrap_or(0) as u8,
+        likes_count: doc.get_i32("likes_count").ok().unwrap_or(0),
+        is_deleted: doc.get_bool("is_deleted").ok().unwrap_or(false),
+        created_at: doc.get_datetime("created_at")
+            .ok()
+            .map(|dt| dt.timestamp_millis())
+            .unwrap_or(0),
+        updated_at: doc.get_datetime("updated_at")
+            .ok()
+            .map(|dt| dt.timestamp_millis())
+            .unwrap_or(0),
+    })
+}
+
+fn doc_to_aspect(doc: &Document) -> Option<AspectSentiment> {
+    Some(AspectSentiment {
+        aspect: Aspect::parse(doc.get_str("aspect").ok()?)?,
+        status: doc.get_i32("status").ok()? as u8,
+        weight: doc.get_f64("weight").ok()?,
+    })
+}
+
+fn doc_to_term(doc: &Document) -> Option<SentimentTerm> {
+    Some(SentimentTerm {
+        term: doc.get_str("term").ok()?.to_string(),
+        weight: doc.get_f64("weight").ok()?,
+        start: doc.get_i64("start").ok()? as usize,
+        end: doc.get_i64("end").ok()? as usize,
+    })
+}
+
+fn doc_to_sentence(doc: &Document) -> Option<SentenceSentiment> {
+    Some(SentenceSentiment {
+        start: doc.get_i64("start").ok()? as usize,
+        end: doc.get_i64("end").ok()? as usize,
+        status: doc.get_i32("status").ok()? as u8,
+        scoring: doc.get_i32("scoring").ok()? as u8,
+    })
+}
+
+fn doc_to_keyphrase(doc: &Document) -> Option<Keyphrase> {
+    Some(Keyphrase {
+        phrase: doc.get_str("phrase").ok()?.to_string(),
+        status: doc.get_i32("status").ok().unwrap_or(2) as u8,
+    })
+}
+
+fn doc_to_label_override(doc: &Document) -> Option<LabelOverride> {
+    Some(LabelOverride {
+        moderator: doc.get_str("moderator").ok()?.to_string(),
+        at: doc.get_datetime("at").ok()?.timestamp_millis(),
+        old_status: doc.get_i32("old_status").ok()? as u8,
+        new_status: doc.get_i32("new_status").ok()? as u8,
+    })
+}
+
+fn doc_to_toxic_term(doc: &Document) -> Option<ToxicTerm> {
+    Some(ToxicTerm {
+        term: doc.get_str("term").ok()?.to_string(),
+        category: ToxicityCategory::parse(doc.get_str("category").ok()?)?,
+        start: doc.get_i64("start").ok()? as usize,
+        end: doc.get_i64("end").ok()? as usize,
+    })
+}
+
+fn label_override_doc(change: &LabelOverride) -> Document {
+    doc! {
+        "moderator": &change.moderator,
+        "at": bson::DateTime::from_millis(change.at),
+        "old_status": change.old_status as i32,
+        "new_status": change.new_status as i32,
+    }
+}
+
+fn toxicity_doc(fields: &ToxicityFields) -> Document {
+    let terms: Vec<Document> = fields
+        .toxic_terms
+        .iter()
+        .map(|t| doc! { "term": &t.term, "category": t.category.as_str(), "start": t.start as i64, "end": t.end as i64 })
+        .collect();
+    doc! {
+        "toxicity": fields.toxicity as i32,
+        "toxic_terms": terms,
+        "moderation": fields.moderation.as_str(),
+    }
+}
+
+/// Sentiment fields to `$set`; the optional ones are only present when
+/// they have a value.
+fn analysis_doc(fields: &AnalysisFields) -> Document {
+    let aspects: Vec<Document> = fields
+        .aspects
+        .iter()
+        .map(|a| doc! { "aspect": a.aspect.as_str(), "status": a.status as i32, "weight": a.weight })
+        .collect();
+    let evidence: Vec<Document> = fields
+        .evidence
+        .iter()
+        .map(|e| doc! { "term": &e.term, "weight": e.weight, "start": e.start as i64, "end": e.end as i64 })
+        .collect();
+    let sentences: Vec<Document> = fields
+        .sentences
+        .iter()
+        .map(|s| doc! {
+            "start": s.start as i64,
+            "end": s.end as i64,
+            "status": s.status as i32,
+            "scoring": s.scoring as i32,
+        })
+        .collect();
+    let keyphrases: Vec<Document> = fields
+        .keyphrases
+        .iter()
+        .map(|k| doc! { "phrase": &k.phrase, "status": k.status as i32 })
+        .collect();
+    let mut doc = doc! {
+        "status": fields.status as i32,
+        "auto_status": fields.auto_status as i32,
+        "confidence": fields.confidence as f64,
+        "needs_review": fields.needs_review,
+        "scoring": fields.scoring as i32,
+        "unanalyzed": fields.unanalyzed,
+        "aspects": aspects,
+        "evidence": evidence,
+        "sentences": sentences,
+        "keyphrases": keyphrases,
+    };
+    if let Some(human_status) = fields.human_status {
+        doc.insert("human_status", human_status as i32);
+    }
+    if let Some(language) = &fields.language {
+        doc.insert("language", language);
+    }
+    if let Some(version) = &fields.analyzer_version {
+        doc.insert("analyzer_version", version);
+    }
+    doc
+}
+
+/// Update document that replaces the stored analysis with `fields`, plus
+/// any extra `$set` fields, unsetting the optional fields without a value.
+fn analysis_update(fields: &AnalysisFields, mut set: Document, mut unset: Document) -> Document {
+    set.extend(analysis_doc(fields));
+    for (name, value) in [
+        ("human_status", fields.human_status.is_none()),
+        ("language", fields.language.is_none()),
+        ("analyzer_version", fields.analyzer_version.is_none()),
+    ] {
+        if value {
+            unset.insert(name, "");
+        }
+    }
+
+    let mut update = doc! { "$set": set };
+    if !unset.is_empty() {
+        update.insert("$unset", unset);
+    }
+    update
+}
+
+fn topic_doc(topic: &Topic) -> Document {
+    let examples: Vec<Document> = topic
+        .examples
+        .iter()
+        .map(|e| doc! { "comment_id": &e.comment_id, "content": &e.content })
+        .collect();
+    doc! {
+        "post_id": &topic.post_id,
+        "label": &topic.label,
+        "terms": &topic.terms,
+        "size": topic.size as i32,
+        "examples": examples,
+        "generated_at": bson::DateTime::from_millis(topic.generated_at),
+    }
+}
+
+fn doc_to_topic(doc: &Document) -> Option<Topic> {
+    Some(Topic {
+        post_id: doc.get_str("post_id").ok()?.to_string(),
+        label: doc.get_str("label").ok()?.to_string(),
+        terms: doc.get_array("terms")
+            .map(|a| a.iter().filter_map(|t| t.as_str()).map(str::to_string).collect())
+            .unwrap_or_default(),
+        size: doc.get_i32("size").ok().unwrap_or(0) as u32,
+        examples: doc.get_array("examples")
+            .map(|a| {
+                a.iter()
+                    .filter_map(|b| b.as_document())
+                    .filter_map(|e| {
+                        Some(TopicExample {
+                            comment_id: e.get_str("comment_id").ok()?.to_string(),
+                            content: e.get_str("content").ok()?.to_string(),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        generated_at: doc.get_datetime("generated_at").ok().map(|dt| dt.timestamp_millis()).unwrap_or(0),
+    })
+}
+
+fn collection() -> Collection<Document> {
+    get_comments_collection()
+}
+
+async fn find(filter: Document, sort: Document, limit: Option<i64>) -> Result<Vec<Comment>, CommentError> {
+    let col = collection();
+    let mut find = col.find(filter).sort(sort);
+    if let Some(limit) = limit {
+        find = find.limit(limit);
+    }
+    let mut cursor = find.await.map_err(|e| storage_error("Find", e))?;
+
+    let mut comments = Vec::new();
+    while let Some(doc) = cursor.try_next().await.map_err(|e| storage_error("Cursor", e))? {
+        if let Some(comment) = doc_to_comment(&doc) {
+            comments.push(comment);
+        }
+    }
+
+    Ok(comments)
+}
+
+/// Apply `update` to the comment with `id`.
+async fn update_one(id: &str, update: Document, action: &str) -> Result<(), CommentError> {
+    let oid = parse_id(id)?;
+    let result = collection()
+        .update_one(doc! { "_id": oid }, update)
+        .await
+        .map_err(|e| storage_error(action, e))?;
+    if result.matched_count == 0 {
+        return Err(not_found());
+    }
+    Ok(())
+}
+
+/// Filter for a post's non-deleted, non-hidden comments.
+fn visible_on(post_id: &str) -> Document {
+    doc! {
+        "post_id": post_id,
+        "is_deleted": false,
+        "moderation": { "$ne": ModerationStatus::Hidden.as_str() },
+    }
+}
+
+#[async_trait]
+impl CommentStore for MongoStore {
+    fn name(&self) -> &'static str {
+        "mongodb"
+    }
+
+    async fn create(&self, mut comment: Comment, content_original: Option<String>) -> Result<Comment, CommentError> {
+        let parent_oid = comment.parent_id.as_deref().map(parse_id).transpose()?;
+
+        // Store post_id as a string (supports both ObjectId and human-readable IDs)
+        let mut doc = doc! {
+            "post_id": &comment.post_id,
+            "author_public_key": &comment.author_public_key,
+            "content": &comment.content,
+            "redacted": comment.redacted,
+            "depth": comment.depth as i32,
+            "likes_count": comment.likes_count,
+            "spam_score": comment.spam_score as i32,
+            "is_deleted": comment.is_deleted,
+            "created_at": bson::DateTime::from_millis(comment.created_at),
+            "updated_at": bson::DateTime::from_millis(comment.updated_at),
+        };
+        doc.extend(analysis_doc(&AnalysisFields::of(&comment)));
+        doc.extend(toxicity_doc(&ToxicityFields {
+            toxicity: comment.toxicity,
+            toxic_terms: comment.toxic_terms.clone(),
+            moderation: comment.moderation,
+        }));
+        if !comment.label_overrides.is_empty() {
+            let changes: Vec<Document> = comment.label_overrides.iter().map(label_override_doc).collect();
+            doc.insert("label_overrides", changes);
+        }
+        if let Some(pid) = parent_oid {
+            doc.insert("parent_id", pid);
+        }
+        if let Some(sealed) = content_original {
+            doc.insert("content_original", sealed);
+        }
+
+        let result = collection().insert_one(&doc).await.map_err(|e| storage_error("Insert", e))?;
+        let inserted_id = result
+            .inserted_id
+            .as_object_id()
+            .ok_or_else(|| CommentError::Storage("No inserted ID returned".to_string()))?;
+
+        comment.id = Some(inserted_id.to_hex());
+        Ok(comment)
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<Comment>, CommentError> {
+        let oid = parse_id(id)?;
+
+        let doc = collection()
+            .find_one(doc! { "_id": oid })
+            .await
+            .map_err(|e| storage_error("Find", e))?;
+
+        Ok(doc.as_ref().and_then(doc_to_comment))
+    }
+
+    async fn list_by_post(&self, post_id: &str, since: Option<i64>) -> Result<Vec<Comment>, CommentError> {
+        let mut filter = visible_on(post_id);
+        if let Some(since) = since {
+            filter.insert("created_at", doc! { "$gte": bson::DateTime::from_millis(since) });
+        }
+        find(filter, doc! { "created_at": 1 }, None).await
+    }
+
+    async fn list_recent(&self, post_id: &str, since: i64, limit: usize) -> Result<Vec<Comment>, CommentError> {
+        let filter = doc! {
+            "post_id": post_id,
+            "is_deleted": false,
+            "created_at": { "$gte": bson::DateTime::from_millis(since) },
+        };
+        find(filter, doc! { "created_at": -1 }, Some(limit as i64)).await
+    }
+
+    async fn count_by_author_since(&self, author_public_key: &str, since: i64) -> Result<usize, CommentError> {
+        let count = collection()
+            .count_documents(doc! {
+                "author_public_key": author_public_key,
+                "created_at": { "$gte": bson::DateTime::from_millis(since) },
+            })
+            .await
+            .map_err(|e| storage_error("Count", e))?;
+
+        Ok(count as usize)
+    }
+
+    async fn update(&self, id: &str, edit: &ContentEdit) -> Result<(), CommentError> {
+        let mut set = doc! {
+            "content": &edit.content,
+            "redacted": edit.redacted,
+            "spam_score": edit.spam_score as i32,
+            "updated_at": bson::DateTime::from_millis(edit.updated_at),
+        };
+        let mut toxicity = toxicity_doc(&edit.toxicity);
+        toxicity.remove("moderation");
+        set.extend(toxicity);
+        let mut unset = Document::new();
+        match &edit.content_original {
+            Some(sealed) => {
+                set.insert("content_original", sealed);
+            }
+            None => {
+                unset.insert("content_original", "");
+            }
+        }
+        let mut update = analysis_update(&edit.analysis, set, unset);
+        // Names sort from most to least severe ("hidden" < "queued" <
+        // "visible"), so `$min` keeps the more severe status, as
+        // `ContentEdit::apply` does
+        update.insert("$min", doc! { "moderation": edit.toxicity.moderation.as_str() });
+        update_one(id, update, "Update").await
+    }
+
+    async fn set_analysis(&self, id: &str, analysis: &AnalysisFields) -> Result<(), CommentError> {
+        update_one(id, analysis_update(analysis, Document::new(), Document::new()), "Update").await
+    }
+
+    async fn like(&self, id: &str) -> Result<(), CommentError> {
+        let update = doc! {
+            "$inc": { "likes_count": 1 },
+            "$set": { "updated_at": bson::DateTime::now() },
+        };
+        update_one(id, update, "Like").await
+    }
+
+    async fn soft_delete(&self, id: &str) -> Result<(), CommentError> {
+        let update = doc! {
+            "$set": {
+                "is_deleted": true,
+                "updated_at": bson::DateTime::now(),
+            }
+        };
+        update_one(id, update, "Delete").await
+    }
+
+    async fn set_moderation(&self, id: &str, moderation: ModerationStatus) -> Result<(), CommentError> {
+        update_one(id, doc! { "$set": { "moderation": moderation.as_str() } }, "Moderation").await
+    }
+
+    async fn set_human_label(&self, id: &str, change: &LabelOverride) -> Result<(), CommentError> {
+        let status = change.new_status as i32;
+        let update = doc! {
+            "$set": { "status": status, "human_status": status, "needs_review": false },
+            "$push": { "label_overrides": label_override_doc(change) },
+        };
+        update_one(id, update, "Label").await
+    }
+
+    async fn list_negative(&self, post_id: &str) -> Result<Vec<Comment>, CommentError> {
+        let mut filter = visible_on(post_id);
+        filter.insert("status", 1_i32);
+        find(filter, doc! { "created_at": -1 }, None).await
+    }
+
+    async fn list_replies(&self, parent_ids: &[String]) -> Result<Vec<Comment>, CommentError> {
+        if parent_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let parents = parent_ids.iter().map(|id| parse_id(id)).collect::<Result<Vec<_>, _>>()?;
+        let filter = doc! {
+            "parent_id": { "$in": parents },
+            "is_deleted": false,
+            "moderation": { "$ne": ModerationStatus::Hidden.as_str() },
+        };
+        find(filter, doc! { "created_at": 1, "_id": 1 }, None).await
+    }
+
+    async fn list_page(
+        &self,
+        listing: &Listing,
+        sort: CommentSort,
+        after: Option<&PageCursor>,
+        limit: usize,
+    ) -> Result<Vec<Comment>, CommentError> {
+        let mut filter = match listing {
+            Listing::Post { post_id, status, top_level } => {
+                let mut filter = visible_on(post_id);
+                if let Some(status) = status {
+                    filter.insert("status", *status as i32);
+                }
+                if *top_level {
+                    // Matches a missing field too
+                    filter.insert("parent_id", Bson::Null);
+                }
+                filter
+            }
+            Listing::All => Document::new(),
+        };
+        let (field, direction, beyond) = match sort {
+            CommentSort::Newest => ("created_at", -1, "$lt"),
+            CommentSort::Oldest => ("created_at", 1, "$gt"),
+            CommentSort::MostLiked => ("likes_count", -1, "$lt"),
+            CommentSort::MostNegative => ("scoring", 1, "$gt"),
+        };
+        if let Some(after) = after {
+            let key = match sort {
+                CommentSort::Newest | CommentSort::Oldest => Bson::DateTime(bson::DateTime::from_millis(after.key)),
+                CommentSort::MostLiked | CommentSort::MostNegative => Bson::Int64(after.key),
+            };
+            let oid = parse_id(&after.id)?;
+            filter.insert(
+                "$or",
+                vec![doc! { field: { beyond: key.clone() } }, doc! { field: key, "_id": { beyond: oid } }],
+            );
+        }
+        find(filter, doc! { field: direction, "_id": direction }, Some(limit as i64)).await
+    }
+
+    async fn list_after(&self, after: Option<&str>, limit: usize) -> Result<Vec<Comment>, CommentError> {
+        let filter = match after {
+            Some(id) => doc! { "_id": { "$gt": parse_id(id)? } },
+            None => doc! {},
+        };
+        find(filter, doc! { "_id": 1 }, Some(limit as i64)).await
+    }
+
+    async fn moderation_queue(&self) -> Result<Vec<Comment>, CommentError> {
+        let filter = doc! {
+            "is_deleted": false,
+            "moderation": { "$in": [ModerationStatus::Queued.as_str(), ModerationStatus::Hidden.as_str()] },
+        };
+        find(filter, doc! { "toxicity": -1, "created_at": -1 }, None).await
+    }
+
+    async fn review_queue(&self) -> Result<Vec<Comment>, CommentError> {
+        let filter = doc! {
+            "is_deleted": false,
+            "needs_review": true,
+        };
+        find(filter, doc! { "confidence": 1, "created_at": -1 }, None).await
+    }
+
+    async fn list_human_labelled(&self) -> Result<Vec<Comment>, CommentError> {
+        let filter = doc! {
+            "is_deleted": false,
+            "human_status": { "$exists": true },
+        };
+        find(filter, doc! { "created_at": 1 }, None).await
+    }
+
+    async fn aspect_summary(&self, post_id: &str) -> Result<Vec<AspectSummary>, CommentError> {
+        let count = |status: i32| doc! { "$sum": { "$cond": [{ "$eq": ["$aspects.status", status] }, 1, 0] } };
+        let pipeline = vec![
+            doc! { "$match": visible_on(post_id) },
+            doc! { "$unwind": "$aspects" },
+            doc! { "$group": {
+                "_id": "$aspects.aspect",
+                "negative": count(1),
+                "neutral": count(2),
+                "positive": count(3),
+            } },
+        ];
+
+        let mut cursor = collection()
+            .aggregate(pipeline)
+            .await
+            .map_err(|e| storage_error("Aggregate", e))?;
+
+        let mut summaries = Vec::new();
+        while let Some(doc) = cursor.try_next().await.map_err(|e| storage_error("Cursor", e))? {
+            let Some(aspect) = doc.get_str("_id").ok().and_then(Aspect::parse) else {
+                continue;
+            };
+            let field = |name: &str| doc.get_i32(name).ok().unwrap_or(0) as u32;
+            summaries.push(AspectSummary {
+                aspect,
+                negative: field("negative"),
+                neutral: field("neutral"),
+                positive: field("positive"),
+            });
+        }
+        summaries.sort_by_key(|s| s.aspect);
+
+        Ok(summaries)
+    }
+
+    async fn top_keyphrases(&self, post_id: &str, since: Option<i64>, limit: usize) -> Result<TopKeyphrases, CommentError> {
+        let mut filter = visible_on(post_id);
+        if let Some(since) = since {
+            filter.insert("created_at", doc! { "$gte": bson::DateTime::from_millis(since) });
+        }
+        let limit = limit as i64;
+        let top = |status: i32| vec![doc! { "$match": { "_id.status": status } }, doc! { "$limit": limit }];
+        let pipeline = vec![
+            doc! { "$match": filter },
+            doc! { "$unwind": "$keyphrases" },
+            doc! { "$group": {
+                "_id": { "phrase": "$keyphrases.phrase", "status": "$keyphrases.status" },
+                "count": { "$sum": 1 },
+            } },
+            doc! { "$sort": { "count": -1, "_id.phrase": 1 } },
+            doc! { "$facet": { "negative": top(1), "neutral": top(2), "positive": top(3) } },
+        ];
+
+        let mut cursor = collection()
+            .aggregate(pipeline)
+            .await
+            .map_err(|e| storage_error("Aggregate", e))?;
+
+        let mut top_keyphrases = TopKeyphrases::default();
+        if let Some(doc) = cursor.try_next().await.map_err(|e| storage_error("Cursor", e))? {
+            let counts = |class: &str| -> Vec<PhraseCount> {
+                doc.get_array(class)
+                    .map(|a| {
+                        a.iter()
+                            .filter_map(|b| b.as_document())
+                            .filter_map(|d| {
+                                Some(PhraseCount {
+                                    phrase: d.get_document("_id").ok()?.get_str("phrase").ok()?.to_string(),
+                                    count: d.get_i32("count").ok()? as u32,
+                                })
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default()
+            };
+            top_keyphrases.negative = counts("negative");
+            top_keyphrases.neutral = counts("neutral");
+            top_keyphrases.positive = counts("positive");
+        }
+
+        Ok(top_keyphrases)
+    }
+
+    async fn replace_topics(&self, post_id: &str, topics: &[Topic]) -> Result<(), CommentError> {
+        let col = get_topics_collection();
+        col.delete_many(doc! { "post_id": post_id })
+            .await
+            .map_err(|e| storage_error("Delete", e))?;
+        if !topics.is_empty() {
+            col.insert_many(topics.iter().map(topic_doc))
+                .await
+                .map_err(|e| storage_error("Insert", e))?;
+        }
+        Ok(())
+    }
+
+    async fn list_topics(&self, post_id: &str) -> Result<Vec<Topic>, CommentError> {
+        let mut cursor = get_topics_collection()
+            .find(doc! { "post_id": post_id })
+            .sort(doc! { "size": -1 })
+            .await
+            .map_err(|e| storage_error("Find", e))?;
+
+        let mut topics = Vec::new();
+        while let Some(doc) = cursor.try_next().await.map_err(|e| storage_error("Cursor", e))? {
+            if let Some(topic) = doc_to_topic(&doc) {
+                topics.push(topic);
+            }
+        }
+
+        Ok(topics)
+    }
+}
@@ -0,0 +1,490 @@
+//! Comments in an embedded SQLite database, to run without a MongoDB
+//! cluster.
+//!
+//! Each comment is one row: the whole comment as JSON in `data`, plus
+//! copies of the fields most queries filter and sort on. Topics are stored
+//! the same way in their own table.
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use bson::oid::ObjectId;
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Transaction};
+use crate::model::{AspectSummary, Comment, CommentError, CommentSort, LabelOverride, ModerationStatus, TopKeyphrases, Topic};
+use super::{
+    not_found, parse_id, storage_error, tally_aspects, tally_keyphrases, AnalysisFields, CommentStore, ContentEdit,
+    Listing, PageCursor,
+};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS comments (
+    id TEXT PRIMARY KEY,
+    post_id TEXT NOT NULL,
+    author_public_key TEXT NOT NULL,
+    status INTEGER NOT NULL,
+    human_status INTEGER,
+    needs_review INTEGER NOT NULL,
+    confidence REAL NOT NULL,
+    toxicity INTEGER NOT NULL,
+    moderation TEXT NOT NULL,
+    is_deleted INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    content_original TEXT,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS comments_post ON comments (post_id, created_at);
+CREATE INDEX IF NOT EXISTS comments_author ON comments (author_public_key, created_at);
+CREATE INDEX IF NOT EXISTS comments_parent ON comments (json_extract(data, '$.parent_id'));
+CREATE TABLE IF NOT EXISTS topics (
+    post_id TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS topics_post ON topics (post_id, size);
+";
+
+/// The expression a listing in `sort` order is ordered by.
+fn sort_column(sort: CommentSort) -> &'static str {
+    match sort {
+        CommentSort::Newest | CommentSort::Oldest => "created_at",
+        CommentSort::MostLiked => "json_extract(data, '$.likes_count')",
+        CommentSort::MostNegative => "json_extract(data, '$.scoring')",
+    }
+}
+
+/// The parent of a reply; indexed.
+const PARENT: &str = "json_extract(data, '$.parent_id')";
+
+/// Live, non-hidden comments.
+const VISIBLE: &str = "is_deleted = 0 AND moderation <> 'hidden'";
+
+/// A shared connection; queries run on the blocking thread pool.
+pub struct SqliteStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+fn sql_error(e: rusqlite::Error) -> CommentError {
+    storage_error("SQLite", e)
+}
+
+fn decode(id: String, data: &str) -> Result<Comment, CommentError> {
+    let mut comment: Comment =
+        serde_json::from_str(data).map_err(|e| CommentError::Storage(format!("Stored comment {} is malformed: {}", id, e)))?;
+    comment.id = Some(id);
+    Ok(comment)
+}
+
+/// Comments of the rows `sql` selects; it must select `id, data`.
+fn query(conn: &Connection, sql: &str, params: impl rusqlite::Params) -> Result<Vec<Comment>, CommentError> {
+    let mut statement = conn.prepare(sql).map_err(sql_error)?;
+    let rows = statement
+        .query_map(params, |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+        .map_err(sql_error)?;
+    rows.map(|row| {
+        let (id, data) = row.map_err(sql_error)?;
+        decode(id, &data)
+    })
+    .collect()
+}
+
+/// Write back every column of a stored comment but `content_original`.
+fn save(tx: &Transaction, comment: &Comment) -> Result<(), CommentError> {
+    let data = serde_json::to_string(comment).map_err(|e| CommentError::Storage(format!("Cannot encode comment: {}", e)))?;
+    tx.execute(
+        "UPDATE comments SET status = ?2, human_status = ?3, needs_review = ?4, confidence = ?5, toxicity = ?6,
+             moderation = ?7, is_deleted = ?8, data = ?9
+         WHERE id = ?1",
+        params![
+            comment.id,
+            comment.status,
+            comment.human_status,
+            comment.needs_review,
+            comment.confidence,
+            comment.toxicity,
+            comment.moderation.as_str(),
+            comment.is_deleted,
+            data,
+        ],
+    )
+    .map_err(sql_error)?;
+    Ok(())
+}
+
+/// Change the comment with `id` in one transaction; `change` may also
+/// write to its row.
+fn modify(
+    conn: &mut Connection,
+    id: &str,
+    change: impl FnOnce(&Transaction, &mut Comment) -> rusqlite::Result<()>,
+) -> Result<(), CommentError> {
+    parse_id(id)?;
+    let tx = conn.transaction().map_err(sql_error)?;
+    let data: String = tx
+        .query_row("SELECT data FROM comments WHERE id = ?1", [id], |row| row.get(0))
+        .optional()
+        .map_err(sql_error)?
+        .ok_or_else(not_found)?;
+    let mut comment = decode(id.to_string(), &data)?;
+    change(&tx, &mut comment).map_err(sql_error)?;
+    save(&tx, &comment)?;
+    tx.commit().map_err(sql_error)
+}
+
+impl SqliteStore {
+    /// Open (or create) the database file at `path`.
+    pub fn open(path: &Path) -> Result<Self, String> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+        }
+        let conn = Connection::open(path).map_err(|e| format!("Cannot open {}: {}", path.display(), e))?;
+        Self::with_connection(conn)
+    }
+
+    /// A database that lives as long as the store.
+    pub fn open_in_memory() -> Result<Self, String> {
+        Self::with_connection(Connection::open_in_memory().map_err(sql_error)?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self, String> {
+        conn.execute_batch(SCHEMA).map_err(sql_error)?;
+        Ok(SqliteStore { conn: Arc::new(Mutex::new(conn)) })
+    }
+
+    /// Run `f` with the connection on the blocking thread pool.
+    async fn run<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&mut Connection) -> Result<T, CommentError> + Send + 'static,
+    ) -> Result<T, CommentError> {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut conn = conn.lock().map_err(|_| CommentError::Storage("SQLite connection poisoned".to_string()))?;
+            f(&mut conn)
+        })
+        .await
+        .map_err(|e| CommentError::Storage(format!("SQLite task failed: {}", e)))?
+    }
+}
+
+#[async_trait]
+impl CommentStore for SqliteStore {
+    fn name(&self) -> &'static str {
+        "sqlite"
+    }
+
+    async fn create(&self, mut comment: Comment, content_original: Option<String>) -> Result<Comment, CommentError> {
+        comment.id = Some(ObjectId::new().to_hex());
+        let stored = comment.clone();
+        self.run(move |conn| {
+            let data = serde_json::to_string(&stored).map_err(|e| CommentError::Storage(format!("Cannot encode comment: {}", e)))?;
+            conn.execute(
+                "INSERT INTO comments (id, post_id, author_public_key, status, human_status, needs_review, confidence,
+                     toxicity, moderation, is_deleted, created_at, content_original, data)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+                params![
+                    stored.id,
+                    stored.post_id,
+                    stored.author_public_key,
+                    stored.status,
+                    stored.human_status,
+                    stored.needs_review,
+                    stored.confidence,
+                    stored.toxicity,
+                    stored.moderation.as_str(),
+                    stored.is_deleted,
+                    stored.created_at,
+                    content_original,
+                    data,
+                ],
+            )
+            .map_err(|e| storage_error("Insert", e))?;
+            Ok(())
+        })
+        .await?;
+        Ok(comment)
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<Comment>, CommentError> {
+        parse_id(id)?;
+        let id = id.to_string();
+        self.run(move |conn| Ok(query(conn, "SELECT id, data FROM comments WHERE id = ?1", [id])?.pop()))
+            .await
+    }
+
+    async fn list_by_post(&self, post_id: &str, since: Option<i64>) -> Result<Vec<Comment>, CommentError> {
+        let post_id = post_id.to_string();
+        self.run(move |conn| {
+            query(
+                conn,
+                &format!(
+                    "SELECT id, data FROM comments WHERE post_id = ?1 AND {} AND created_at >= ?2 ORDER BY created_at",
+                    VISIBLE
+                ),
+                params![post_id, since.unwrap_or(i64::MIN)],
+            )
+        })
+        .await
+    }
+
+    async fn list_recent(&self, post_id: &str, since: i64, limit: usize) -> Result<Vec<Comment>, CommentError> {
+        let post_id = post_id.to_string();
+        self.run(move |conn| {
+            query(
+                conn,
+                "SELECT id, data FROM comments WHERE post_id = ?1 AND is_deleted = 0 AND created_at >= ?2
+                 ORDER BY created_at DESC LIMIT ?3",
+                params![post_id, since, limit as i64],
+            )
+        })
+        .await
+    }
+
+    async fn count_by_author_since(&self, author_public_key: &str, since: i64) -> Result<usize, CommentError> {
+        let author_public_key = author_public_key.to_string();
+        self.run(move |conn| {
+            conn.query_row(
+                "SELECT COUNT(*) FROM comments WHERE author_public_key = ?1 AND created_at >= ?2",
+                params![author_public_key, since],
+                |row| row.get::<_, i64>(0),
+            )
+            .map(|count| count as usize)
+            .map_err(sql_error)
+        })
+        .await
+    }
+
+    async fn update(&self, id: &str, edit: &ContentEdit) -> Result<(), CommentError> {
+        let (id, edit) = (id.to_string(), edit.clone());
+        self.run(move |conn| {
+            modify(conn, &id, |tx, comment| {
+                edit.apply(comment);
+                tx.execute(
+                    "UPDATE comments SET content_original = ?2 WHERE id = ?1",
+                    params![id, edit.content_original],
+                )?;
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    async fn set_analysis(&self, id: &str, analysis: &AnalysisFields) -> Result<(), CommentError> {
+        let (id, analysis) = (id.to_string(), analysis.clone());
+        self.run(move |conn| {
+            modify(conn, &id, |_, comment| {
+                analysis.apply(comment);
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    async fn like(&self, id: &str) -> Result<(), CommentError> {
+        let id = id.to_string();
+        let now = bson::DateTime::now().timestamp_millis();
+        self.run(move |conn| {
+            modify(conn, &id, |_, comment| {
+                comment.likes_count += 1;
+                comment.updated_at = now;
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    async fn soft_delete(&self, id: &str) -> Result<(), CommentError> {
+        let id = id.to_string();
+        let now = bson::DateTime::now().timestamp_millis();
+        self.run(move |conn| {
+            modify(conn, &id, |_, comment| {
+                comment.is_deleted = true;
+                comment.updated_at = now;
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    async fn set_moderation(&self, id: &str, moderation: ModerationStatus) -> Result<(), CommentError> {
+        let id = id.to_string();
+        self.run(move |conn| {
+            modify(conn, &id, |_, comment| {
+                comment.moderation = moderation;
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    async fn set_human_label(&self, id: &str, change: &LabelOverride) -> Result<(), CommentError> {
+        let (id, change) = (id.to_string(), change.clone());
+        self.run(move |conn| {
+            modify(conn, &id, |_, comment| {
+                comment.status = change.new_status;
+                comment.human_status = Some(change.new_status);
+                comment.needs_review = false;
+                comment.label_overrides.push(change);
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    async fn list_negative(&self, post_id: &str) -> Result<Vec<Comment>, CommentError> {
+        let post_id = post_id.to_string();
+        self.run(move |conn| {
+            query(
+                conn,
+                &format!(
+                    "SELECT id, data FROM comments WHERE post_id = ?1 AND status = 1 AND {} ORDER BY created_at DESC",
+                    VISIBLE
+                ),
+                [post_id],
+            )
+        })
+        .await
+    }
+
+    async fn list_replies(&self, parent_ids: &[String]) -> Result<Vec<Comment>, CommentError> {
+        if parent_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let placeholders = vec!["?"; parent_ids.len()].join(", ");
+        let sql = format!(
+            "SELECT id, data FROM comments WHERE {} IN ({}) AND {} ORDER BY created_at, id",
+            PARENT, placeholders, VISIBLE
+        );
+        let parent_ids = parent_ids.to_vec();
+        self.run(move |conn| query(conn, &sql, params_from_iter(parent_ids))).await
+    }
+
+    async fn list_page(
+        &self,
+        listing: &Listing,
+        sort: CommentSort,
+        after: Option<&PageCursor>,
+        limit: usize,
+    ) -> Result<Vec<Comment>, CommentError> {
+        let mut conditions = Vec::new();
+        let mut values: Vec<Value> = Vec::new();
+        if let Listing::Post { post_id, status, top_level } = listing {
+            conditions.push(format!("post_id = ? AND {}", VISIBLE));
+            values.push(post_id.clone().into());
+            if let Some(status) = status {
+                conditions.push("status = ?".to_string());
+                values.push(i64::from(*status).into());
+            }
+            if *top_level {
+                conditions.push(format!("{} IS NULL", PARENT));
+            }
+        }
+        let key = sort_column(sort);
+        let (direction, beyond) = if sort.descending() { ("DESC", "<") } else { ("ASC", ">") };
+        if let Some(after) = after {
+            conditions.push(format!("({key} {beyond} ? OR ({key} = ? AND id {beyond} ?))"));
+            values.extend([after.key.into(), after.key.into(), after.id.clone().into()]);
+        }
+        values.push((limit as i64).into());
+
+        let filter = if conditions.is_empty() { "1".to_string() } else { conditions.join(" AND ") };
+        let sql =
+            format!("SELECT id, data FROM comments WHERE {filter} ORDER BY {key} {direction}, id {direction} LIMIT ?");
+        self.run(move |conn| query(conn, &sql, params_from_iter(values))).await
+    }
+
+    async fn list_after(&self, after: Option<&str>, limit: usize) -> Result<Vec<Comment>, CommentError> {
+        if let Some(after) = after {
+            parse_id(after)?;
+        }
+        let after = after.unwrap_or_default().to_string();
+        self.run(move |conn| {
+            query(
+                conn,
+                "SELECT id, data FROM comments WHERE id > ?1 ORDER BY id LIMIT ?2",
+                params![after, limit as i64],
+            )
+        })
+        .await
+    }
+
+    async fn moderation_queue(&self) -> Result<Vec<Comment>, CommentError> {
+        self.run(|conn| {
+            query(
+                conn,
+                "SELECT id, data FROM comments WHERE is_deleted = 0 AND moderation IN ('queued', 'hidden')
+                 ORDER BY toxicity DESC, created_at DESC",
+                [],
+            )
+        })
+        .await
+    }
+
+    async fn review_queue(&self) -> Result<Vec<Comment>, CommentError> {
+        self.run(|conn| {
+            query(
+                conn,
+                "SELECT id, data FROM comments WHERE is_deleted = 0 AND needs_review = 1
+                 ORDER BY confidence, created_at DESC",
+                [],
+            )
+        })
+        .await
+    }
+
+    async fn list_human_labelled(&self) -> Result<Vec<Comment>, CommentError> {
+        self.run(|conn| {
+            query(
+                conn,
+                "SELECT id, data FROM comments WHERE is_deleted = 0 AND human_status IS NOT NULL ORDER BY created_at",
+                [],
+            )
+        })
+        .await
+    }
+
+    async fn aspect_summary(&self, post_id: &str) -> Result<Vec<AspectSummary>, CommentError> {
+        let post_id = post_id.to_string();
+        let comments = self
+            .run(move |conn| {
+                query(conn, &format!("SELECT id, data FROM comments WHERE post_id = ?1 AND {}", VISIBLE), [post_id])
+            })
+            .await?;
+        Ok(tally_aspects(&comments))
+    }
+
+    async fn top_keyphrases(&self, post_id: &str, since: Option<i64>, limit: usize) -> Result<TopKeyphrases, CommentError> {
+        let comments = self.list_by_post(post_id, since).await?;
+        Ok(tally_keyphrases(&comments, limit))
+    }
+
+    async fn replace_topics(&self, post_id: &str, topics: &[Topic]) -> Result<(), CommentError> {
+        let (post_id, topics) = (post_id.to_string(), topics.to_vec());
+        self.run(move |conn| {
+            let tx = conn.transaction().map_err(sql_error)?;
+            tx.execute("DELETE FROM topics WHERE post_id = ?1", [&post_id]).map_err(sql_error)?;
+            for topic in &topics {
+                let data =
+                    serde_json::to_string(topic).map_err(|e| CommentError::Storage(format!("Cannot encode topic: {}", e)))?;
+                tx.execute("INSERT INTO topics (post_id, size, data) VALUES (?1, ?2, ?3)", params![post_id, topic.size, data])
+                    .map_err(|e| storage_error("Insert", e))?;
+            }
+            tx.commit().map_err(sql_error)
+        })
+        .await
+    }
+
+    async fn list_topics(&self, post_id: &str) -> Result<Vec<Topic>, CommentError> {
+        let post_id = post_id.to_string();
+        self.run(move |conn| {
+            let mut statement =
+                conn.prepare("SELECT data FROM topics WHERE post_id = ?1 ORDER BY size DESC, rowid").map_err(sql_error)?;
+            let rows = statement.query_map([post_id], |row| row.get::<_, String>(0)).map_err(sql_error)?;
+            rows.map(|row| {
+                let data = row.map_err(sql_error)?;
+                serde_json::from_str(&data).map_err(|e| CommentError::Storage(format!("Stored topic is malformed: {}", e)))
+            })
+            .collect()
+        })
+        .await
+    }
+}
@@ -0,0 +1,214 @@
+//! Extractive summaries of a post's reviews.
+//!
+//! For each sentiment class, the sentences with that label are compared by
+//! TF-IDF and those closest to the class's centre — the most typical
+//! complaint, the most typical praise — are quoted, skipping near-repeats.
+//! Summaries are cached per post and time window; writes to a post's
+//! comments drop its entries.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
+use crate::model::{ClassSummary, Comment, CommentError, ReviewSummary, SummarySentence};
+use super::comment_crud;
+use super::sentiment::{keyphrases, tokenizer, Language};
+use super::tfidf::{centre, dot, vectorize};
+
+/// Most sentences quoted per class.
+pub const MAX_HIGHLIGHTS: usize = 10;
+/// Sentences shorter than this many words ("Wow.") are only quoted when
+/// there is nothing longer.
+const MIN_WORDS: usize = 4;
+/// A sentence this similar to one already quoted is skipped.
+const REPEAT_SIMILARITY: f64 = 0.7;
+/// Cached summaries are recomputed after this long even without writes,
+/// since the time window moves.
+const CACHE_TTL_MILLIS: i64 = 10 * 60 * 1000;
+
+#[derive(Default)]
+struct Cache {
+    /// Bumped on every invalidation, so a summary computed from comments
+    /// read before a write is not cached.
+    generation: u64,
+    /// By (post_id, days, highlights).
+    entries: HashMap<(String, u32, usize), ReviewSummary>,
+}
+
+static CACHE: Lazy<Mutex<Cache>> = Lazy::new(Default::default);
+
+/// Sentences of `comment` with their labels. A comment stored before
+/// sentences were scored counts as one sentence.
+fn labelled_sentences(comment: &Comment) -> Vec<(u8, &str)> {
+    if comment.sentences.is_empty() {
+        return vec![(comment.status, comment.content.as_str())];
+    }
+    comment
+        .sentences
+        .iter()
+        .filter_map(|s| Some((s.status, comment.content.get(s.start..s.end)?)))
+        .collect()
+}
+
+/// The `highlights` sentences most typical of `candidates`.
+fn highlights(candidates: &[(&Comment, &str)], highlights: usize) -> Vec<SummarySentence> {
+    let documents: Vec<Vec<String>> = candidates
+        .iter()
+        .map(|(comment, text)| {
+            let language = comment.language.as_deref().and_then(Language::from_code).unwrap_or(Language::English);
+            keyphrases::terms(text, language)
+        })
+        .collect();
+    let (terms, vectors) = vectorize(&documents, 1);
+    let mean = centre(vectors.iter(), terms.len());
+
+    let mut order: Vec<usize> = (0..candidates.len()).collect();
+    order.sort_by(|a, b| dot(&vectors[*b], &mean).total_cmp(&dot(&vectors[*a], &mean)));
+    let long_enough = |i: &usize| tokenizer::tokenize(candidates[*i].1).len() >= MIN_WORDS;
+    if order.iter().any(long_enough) {
+        order.retain(long_enough);
+    }
+
+    let mut picked: Vec<usize> = Vec::new();
+    for i in order {
+        if picked.len() == highlights {
+            break;
+        }
+        let own = centre(std::iter::once(&vectors[i]), terms.len());
+        if !picked.iter().any(|p| dot(&vectors[*p], &own) >= REPEAT_SIMILARITY) {
+            picked.push(i);
+        }
+    }
+    picked
+        .into_iter()
+        .map(|i| SummarySentence {
+            comment_id: candidates[i].0.id.clone().unwrap_or_default(),
+            text: candidates[i].1.to_string(),
+        })
+        .collect()
+}
+
+/// Summarize `comments`, quoting up to `per_class` sentences per class.
+/// Unanalyzed comments are left out. `post_id`, `days` and `generated_at`
+/// are left for the caller.
+pub fn summarize(comments: &[Comment], per_class: usize) -> ReviewSummary {
+    let comments: Vec<&Comment> = comments.iter().filter(|c| !c.unanalyzed).collect();
+    let class = |status: u8| status.clamp(1, 3) as usize - 1;
+
+    let mut classes: [ClassSummary; 3] = Default::default();
+    let mut candidates: [Vec<(&Comment, &str)>; 3] = Default::default();
+    for comment in &comments {
+        classes[class(comment.status)].comments += 1;
+        for (status, text) in labelled_sentences(comment) {
+            classes[class(status)].sentences += 1;
+            candidates[class(status)].push((comment, text));
+        }
+    }
+    for (summary, candidates) in classes.iter_mut().zip(&candidates) {
+        summary.highlights = highlights(candidates, per_class);
+    }
+
+    let [negative, neutral, positive] = classes;
+    ReviewSummary { comments: comments.len() as u32, negative, neutral, positive, ..Default::default() }
+}
+
+/// Summary of a post's visible reviews from the last `days` days (0 = all
+/// time), with up to `per_class` sentences per class, from the cache when
+/// possible.
+pub async fn get_summary(post_id: &str, days: u32, per_class: usize) -> Result<ReviewSummary, CommentError> {
+    let per_class = per_class.clamp(1, MAX_HIGHLIGHTS);
+    let key = (post_id.to_string(), days, per_class);
+    let now = bson::DateTime::now().timestamp_millis();
+
+    let generation = {
+        let cache = CACHE.lock().map_err(|_| CommentError::Storage("Summary cache poisoned".to_string()))?;
+        if let Some(summary) = cache.entries.get(&key).filter(|s| now - s.generated_at < CACHE_TTL_MILLIS) {
+            return Ok(summary.clone());
+        }
+        cache.generation
+    };
+
+    let comments = comment_crud::get_comments_by_post_since(post_id, days).await?;
+    let summary = ReviewSummary { post_id: post_id.to_string(), days, generated_at: now, ..summarize(&comments, per_class) };
+
+    if let Ok(mut cache) = CACHE.lock() {
+        if cache.generation == generation {
+            cache.entries.insert(key, summary.clone());
+        }
+    }
+    Ok(summary)
+}
+
+/// Drop the cached summaries of a post after its comments changed.
+pub fn invalidate(post_id: &str) {
+    if let Ok(mut cache) = CACHE.lock() {
+        cache.generation += 1;
+        cache.entries.retain(|(post, _, _), _| post != post_id);
+    }
+}
+
+/// Drop every cached summary, when the post that changed is not at hand.
+pub fn clear() {
+    if let Ok(mut cache) = CACHE.lock() {
+        cache.generation += 1;
+        cache.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::SentenceSentiment;
+
+    fn comment(id: &str, content: &str, status: u8, sentences: &[(usize, usize, u8)]) -> Comment {
+        Comment {
+            id: Some(id.to_string()),
+            status,
+            language: Some("en".to_string()),
+            sentences: sentences
+                .iter()
+                .map(|(start, end, status)| SentenceSentiment { start: *start, end: *end, status: *status, scoring: 50 })
+                .collect(),
+            ..Comment::sample(content)
+        }
+    }
+
+    #[test]
+    fn test_summarize_quotes_typical_sentences_per_class() {
+        let mixed = "The terrace is lovely. But the soup was cold and salty.";
+        let comments = [
+            comment("a", mixed, 1, &[(0, 22, 3), (23, mixed.len(), 1)]),
+            comment("b", "Cold soup again, and the bread was stale", 1, &[]),
+            comment("c", "We waited ages for a cold soup", 1, &[]),
+            comment("d", "Parking nearby is a nightmare at weekends", 1, &[]),
+            comment("e", "Lovely terrace with a view of the harbour", 3, &[]),
+            comment("f", "Nice", 3, &[]),
+        ];
+
+        let summary = summarize(&comments, 2);
+        assert_eq!(summary.comments, 6);
+        assert_eq!((summary.negative.comments, summary.negative.sentences), (4, 4));
+        assert_eq!((summary.positive.comments, summary.positive.sentences), (2, 3));
+
+        // the soup complaints are typical, the parking one is not
+        assert_eq!(summary.negative.highlights.len(), 2);
+        assert!(summary.negative.highlights.iter().all(|h| h.text.to_lowercase().contains("soup")));
+        assert!(summary.negative.highlights.iter().all(|h| h.comment_id != "d"));
+
+        // "Nice" is too short to quote while longer praise exists
+        assert!(summary.positive.highlights.iter().all(|h| h.text != "Nice"));
+        assert!(summary.neutral.highlights.is_empty());
+    }
+
+    #[test]
+    fn test_summarize_skips_repeats() {
+        let comments = [
+            comment("a", "The soup was cold tonight", 1, &[]),
+            comment("b", "The soup was cold tonight!", 1, &[]),
+            comment("c", "The waiter was rude to us", 1, &[]),
+        ];
+        let quoted: Vec<String> =
+            summarize(&comments, 3).negative.highlights.into_iter().map(|h| h.comment_id).collect();
+        assert_eq!(quoted.len(), 2);
+        assert!(quoted.contains(&"c".to_string()));
+    }
+}
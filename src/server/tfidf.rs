@@ -0,0 +1,92 @@
+//! TF-IDF vectors for comparing comments and sentences, shared by topic
+//! clustering and review summaries.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Unit-length sparse vector of (term index, weight), by term index.
+pub type Vector = Vec<(usize, f64)>;
+
+fn normalize(weights: &mut [f64]) {
+    let norm = weights.iter().map(|w| w * w).sum::<f64>().sqrt();
+    if norm > 0.0 {
+        weights.iter_mut().for_each(|w| *w /= norm);
+    }
+}
+
+/// TF-IDF vectors of `documents` (each a list of terms) and the terms
+/// their indices refer to. Terms found in fewer than `min_frequency`
+/// documents are dropped, which leaves an empty vector for a document
+/// with nothing in common with the others.
+pub fn vectorize(documents: &[Vec<String>], min_frequency: usize) -> (Vec<String>, Vec<Vector>) {
+    let mut frequency: HashMap<&str, usize> = HashMap::new();
+    for document in documents {
+        for term in document.iter().map(String::as_str).collect::<HashSet<_>>() {
+            *frequency.entry(term).or_default() += 1;
+        }
+    }
+    let mut terms: Vec<&str> = frequency
+        .iter()
+        .filter(|(_, n)| **n >= min_frequency)
+        .map(|(term, _)| *term)
+        .collect();
+    terms.sort_unstable();
+    let index: HashMap<&str, usize> = terms.iter().enumerate().map(|(i, term)| (*term, i)).collect();
+
+    let n = documents.len() as f64;
+    let vectors = documents
+        .iter()
+        .map(|document| {
+            let mut counts: BTreeMap<usize, f64> = BTreeMap::new();
+            for term in document {
+                if let Some(&i) = index.get(term.as_str()) {
+                    *counts.entry(i).or_default() += 1.0;
+                }
+            }
+            let (indices, mut weights): (Vec<usize>, Vec<f64>) = counts
+                .into_iter()
+                .map(|(i, count)| (i, count * ((n / frequency[terms[i]] as f64).ln() + 1.0)))
+                .unzip();
+            normalize(&mut weights);
+            indices.into_iter().zip(weights).collect()
+        })
+        .collect();
+    (terms.into_iter().map(str::to_string).collect(), vectors)
+}
+
+/// Cosine similarity of a vector and a unit-length dense vector.
+pub fn dot(vector: &Vector, centre: &[f64]) -> f64 {
+    vector.iter().map(|(i, w)| w * centre[*i]).sum()
+}
+
+/// Unit-length mean of `vectors` as a dense vector of `dims` weights; all
+/// zeros when there are none.
+pub fn centre<'a>(vectors: impl Iterator<Item = &'a Vector>, dims: usize) -> Vec<f64> {
+    let mut centre = vec![0.0; dims];
+    for vector in vectors {
+        for (i, w) in vector {
+            centre[*i] += w;
+        }
+    }
+    normalize(&mut centre);
+    centre
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vectorize() {
+        let documents: Vec<Vec<String>> = [vec!["soup", "cold"], vec!["soup", "cold", "cold"], vec!["parking"]]
+            .map(|d| d.into_iter().map(String::from).collect())
+            .into();
+        let (terms, vectors) = vectorize(&documents, 2);
+        assert_eq!(terms, ["cold", "soup"]);
+        assert!(vectors[2].is_empty());
+
+        // repeated terms weigh more, and every vector has unit length
+        assert!(vectors[1][0].1 > vectors[1][1].1);
+        let own = centre(std::iter::once(&vectors[1]), terms.len());
+        assert!((dot(&vectors[1], &own) - 1.0).abs() < 1e-9);
+    }
+}
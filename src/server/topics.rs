@@ -0,0 +1,239 @@
+//! Offline clustering of a post's negative comments into themes.
+//!
+//! Each comment becomes a TF-IDF vector over its content words and
+//! multi-word keyphrases, and spherical k-means (cosine similarity) groups
+//! the vectors. A cluster is labelled with its heaviest terms and
+//! illustrated by the comments closest to its centre. `dene-admin topics`
+//! runs the job and replaces the post's topics in the comment store.
+
+use crate::model::{Comment, CommentError, Topic, TopicExample};
+use super::comment_crud;
+use super::sentiment::{keyphrases, Language};
+use super::store::store;
+use super::tfidf::{centre, dot, vectorize, Vector};
+
+/// Most topics per post, whatever the caller asks for.
+pub const MAX_TOPICS: usize = 12;
+/// Terms used by fewer comments than this say nothing about a theme.
+const MIN_DOCUMENT_FREQUENCY: usize = 2;
+const MAX_ITERATIONS: usize = 50;
+/// Terms in a topic's label, and kept on the topic.
+const LABEL_TERMS: usize = 3;
+const TOPIC_TERMS: usize = 8;
+/// Example comments per topic.
+const EXAMPLES: usize = 3;
+
+/// A group of comments, by index into the clustered slice.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cluster {
+    /// Heaviest terms of the cluster's centre first.
+    pub terms: Vec<String>,
+    /// Closest to the centre first.
+    pub members: Vec<usize>,
+}
+
+/// Topics for `n` comments when the caller does not say: about √(n/2).
+fn default_topic_count(n: usize) -> usize {
+    ((n as f64 / 2.0).sqrt().round() as usize).max(1)
+}
+
+/// Index of the centre most similar to `vector`; the first on ties.
+fn nearest(vector: &Vector, centres: &[Vec<f64>]) -> usize {
+    let mut best = (0, f64::MIN);
+    for (i, centre) in centres.iter().enumerate() {
+        let similarity = dot(vector, centre);
+        if similarity > best.1 {
+            best = (i, similarity);
+        }
+    }
+    best.0
+}
+
+/// Spherical k-means. Starting centres are picked farthest-first from the
+/// comment most typical of the whole set, so runs are reproducible.
+/// Returns the cluster of each vector.
+fn kmeans(vectors: &[Vector], dims: usize, k: usize) -> Vec<usize> {
+    let mean = centre(vectors.iter(), dims);
+    let first = nearest_vector(vectors, |v| dot(v, &mean));
+    let mut centres = vec![centre(std::iter::once(&vectors[first]), dims)];
+    while centres.len() < k {
+        let closeness = |v: &Vector| centres.iter().map(|c| dot(v, c)).fold(f64::MIN, f64::max);
+        let next = nearest_vector(vectors, |v| -closeness(v));
+        if closeness(&vectors[next]) >= 1.0 - 1e-9 {
+            // every comment is already a copy of a centre
+            break;
+        }
+        centres.push(centre(std::iter::once(&vectors[next]), dims));
+    }
+
+    let mut assignment = Vec::new();
+    for _ in 0..MAX_ITERATIONS {
+        let next: Vec<usize> = vectors.iter().map(|v| nearest(v, &centres)).collect();
+        if next == assignment {
+            break;
+        }
+        assignment = next;
+        centres = (0..centres.len())
+            .map(|c| centre(vectors.iter().zip(&assignment).filter(|(_, a)| **a == c).map(|(v, _)| v), dims))
+            .collect();
+    }
+    assignment
+}
+
+/// Index of the vector scoring highest; the first on ties.
+fn nearest_vector(vectors: &[Vector], score: impl Fn(&Vector) -> f64) -> usize {
+    let mut best = (0, f64::MIN);
+    for (i, vector) in vectors.iter().enumerate() {
+        let s = score(vector);
+        if s > best.1 {
+            best = (i, s);
+        }
+    }
+    best.0
+}
+
+/// Group `comments` into at most `k` themes (default about √(n/2)),
+/// largest first. Comments sharing no term with any other comment are left
+/// out.
+pub fn cluster(comments: &[Comment], k: Option<usize>) -> Vec<Cluster> {
+    let documents: Vec<Vec<String>> = comments
+        .iter()
+        .map(|c| {
+            let language = c.language.as_deref().and_then(Language::from_code).unwrap_or(Language::English);
+            keyphrases::terms(&c.content, language)
+        })
+        .collect();
+    let (terms, vectors) = vectorize(&documents, MIN_DOCUMENT_FREQUENCY);
+    let usable: Vec<usize> = (0..comments.len()).filter(|i| !vectors[*i].is_empty()).collect();
+    if usable.is_empty() {
+        return Vec::new();
+    }
+    let vectors: Vec<Vector> = usable.iter().map(|i| vectors[*i].clone()).collect();
+    let k = k.unwrap_or_else(|| default_topic_count(vectors.len())).clamp(1, MAX_TOPICS).min(vectors.len());
+    let dims = terms.len();
+    let assignment = kmeans(&vectors, dims, k);
+
+    let mut clusters: Vec<Cluster> = (0..k)
+        .filter_map(|c| {
+            let mut members: Vec<usize> = (0..vectors.len()).filter(|i| assignment[*i] == c).collect();
+            if members.is_empty() {
+                return None;
+            }
+            let centre = centre(members.iter().map(|i| &vectors[*i]), dims);
+            members.sort_by(|a, b| dot(&vectors[*b], &centre).total_cmp(&dot(&vectors[*a], &centre)));
+            let mut order: Vec<usize> = (0..dims).filter(|t| centre[*t] > 0.0).collect();
+            order.sort_by(|a, b| centre[*b].total_cmp(&centre[*a]).then(a.cmp(b)));
+            Some(Cluster {
+                terms: order.into_iter().take(TOPIC_TERMS).map(|t| terms[t].clone()).collect(),
+                members: members.into_iter().map(|i| usable[i]).collect(),
+            })
+        })
+        .collect();
+    clusters.sort_by_key(|c| std::cmp::Reverse(c.members.len()));
+    clusters
+}
+
+/// The first `LABEL_TERMS` terms, skipping any that share a word with one
+/// already taken ("soup" after "cold soup").
+pub fn label(terms: &[String]) -> String {
+    let mut taken: Vec<&str> = Vec::new();
+    for term in terms {
+        let words: Vec<&str> = term.split(' ').collect();
+        if !taken.iter().any(|t| t.split(' ').any(|w| words.contains(&w))) {
+            taken.push(term);
+        }
+        if taken.len() == LABEL_TERMS {
+            break;
+        }
+    }
+    taken.join(", ")
+}
+
+/// Cluster the post's negative comments into at most `k` topics and
+/// replace its stored topics with them.
+pub async fn rebuild(post_id: &str, k: Option<usize>) -> Result<Vec<Topic>, CommentError> {
+    let comments = comment_crud::get_negative_comments(post_id).await?;
+    let generated_at = bson::DateTime::now().timestamp_millis();
+    let topics: Vec<Topic> = cluster(&comments, k)
+        .into_iter()
+        .map(|c| Topic {
+            post_id: post_id.to_string(),
+            label: label(&c.terms),
+            size: c.members.len() as u32,
+            examples: c
+                .members
+                .iter()
+                .take(EXAMPLES)
+                .map(|i| TopicExample {
+                    comment_id: comments[*i].id.clone().unwrap_or_default(),
+                    content: comments[*i].content.clone(),
+                })
+                .collect(),
+            terms: c.terms,
+            generated_at,
+        })
+        .collect();
+
+    store().replace_topics(post_id, &topics).await?;
+    Ok(topics)
+}
+
+/// Stored topics of a post, largest first.
+pub async fn get_topics(post_id: &str) -> Result<Vec<Topic>, CommentError> {
+    store().list_topics(post_id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comment(content: &str) -> Comment {
+        Comment { status: 1, scoring: 20, language: Some("en".to_string()), ..Comment::sample(content) }
+    }
+
+    #[test]
+    fn test_clusters_by_theme() {
+        let comments: Vec<Comment> = [
+            "The soup was cold and bland",
+            "Slow service, we waited an hour for the waiter",
+            "Cold soup again, and the bread was stale",
+            "The waiter ignored us, terribly slow service",
+            "My soup arrived cold",
+            "Service so slow, the waiter forgot our order",
+        ]
+        .into_iter()
+        .map(comment)
+        .collect();
+
+        let clusters = cluster(&comments, Some(2));
+        assert_eq!(clusters.len(), 2);
+        let mut groups: Vec<Vec<usize>> = clusters.iter().map(|c| {
+            let mut members = c.members.clone();
+            members.sort();
+            members
+        }).collect();
+        groups.sort();
+        assert_eq!(groups, [vec![0, 2, 4], vec![1, 3, 5]]);
+
+        let soup = clusters.iter().find(|c| c.members.contains(&0)).unwrap();
+        assert!(label(&soup.terms).contains("soup"), "{:?}", soup.terms);
+
+        // the same input gives the same topics
+        assert_eq!(cluster(&comments, Some(2)), clusters);
+    }
+
+    #[test]
+    fn test_label_skips_overlapping_terms() {
+        let terms: Vec<String> = ["cold soup", "soup", "cold", "bread", "stale", "bland"].map(String::from).into();
+        assert_eq!(label(&terms), "cold soup, bread, stale");
+    }
+
+    #[test]
+    fn test_comments_without_shared_terms_are_left_out() {
+        let comments = [comment("Cold soup"), comment("Cold soup!"), comment("Parking was impossible")];
+        let clusters = cluster(&comments, None);
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].members.len(), 2);
+        assert!(cluster(&[], None).is_empty());
+    }
+}
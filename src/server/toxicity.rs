@@ -0,0 +1,345 @@
+//! Toxicity classification, separate from sentiment.
+//!
+//! A negative review complains about the restaurant; a toxic one swears,
+//! threatens or attacks a person. The word lists live in
+//! `data/moderation/toxicity.toml` (compiled in, or the file named by
+//! `TOXICITY_LEXICON`), and the score thresholds that queue or hide a
+//! comment come from `TOXICITY_QUEUE_THRESHOLD` / `TOXICITY_HIDE_THRESHOLD`.
+
+use std::collections::HashSet;
+use once_cell::sync::OnceCell;
+use serde::Deserialize;
+use crate::model::{ModerationStatus, ToxicTerm, ToxicityCategory};
+use super::sentiment::tokenizer::{collapse_runs, match_phrases, phrase_words, tokenize, Token};
+
+const BUILT_IN: &str = include_str!("../../data/moderation/toxicity.toml");
+
+/// Score added per weight unit of abuse found: one swear word is 25.
+const SCORE_PER_WEIGHT: f64 = 25.0;
+const PROFANITY_WEIGHT: f64 = 1.0;
+/// Insults aimed at a person ("you idiot").
+const ATTACK_WEIGHT: f64 = 2.0;
+/// Insults not aimed at anyone in particular ("stupid prices").
+const UNTARGETED_INSULT_WEIGHT: f64 = 0.5;
+const THREAT_WEIGHT: f64 = 3.0;
+const SLUR_WEIGHT: f64 = 4.0;
+/// How many words before an insult a target word ("you") may be.
+const TARGET_SCOPE: usize = 3;
+
+pub const DEFAULT_QUEUE_THRESHOLD: u8 = 40;
+pub const DEFAULT_HIDE_THRESHOLD: u8 = 80;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ToxicityFile {
+    version: String,
+    #[serde(default)]
+    targets: Vec<String>,
+    #[serde(default)]
+    profanity: Vec<String>,
+    #[serde(default)]
+    insults: Vec<String>,
+    #[serde(default)]
+    threats: Vec<String>,
+    #[serde(default)]
+    slurs: Vec<String>,
+}
+
+/// Kind of list a phrase came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Profanity,
+    Insult,
+    Threat,
+    Slur,
+}
+
+/// Word lists, tokenized for matching.
+#[derive(Debug, Clone)]
+pub struct ToxicityLexicon {
+    pub version: String,
+    targets: HashSet<String>,
+    phrases: Vec<Vec<String>>,
+    /// Parallel to `phrases`: the entry as written and its list.
+    entries: Vec<(String, Kind)>,
+    /// Every word used in a phrase, to resolve elongations ("shiiit").
+    words: HashSet<String>,
+}
+
+impl ToxicityLexicon {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let file: ToxicityFile = toml::from_str(source).map_err(|e| format!("Invalid toxicity lexicon: {}", e))?;
+        let mut lexicon = ToxicityLexicon {
+            version: file.version,
+            targets: file.targets.iter().map(|t| t.to_lowercase()).collect(),
+            phrases: Vec::new(),
+            entries: Vec::new(),
+            words: HashSet::new(),
+        };
+
+        let lists = [
+            (file.profanity, Kind::Profanity),
+            (file.insults, Kind::Insult),
+            (file.threats, Kind::Threat),
+            (file.slurs, Kind::Slur),
+        ];
+        for (list, kind) in lists {
+            for entry in list {
+                let words = phrase_words(&entry);
+                if words.is_empty() {
+                    return Err(format!("Invalid toxicity lexicon: empty entry {:?}", entry));
+                }
+                lexicon.words.extend(words.iter().cloned());
+                lexicon.phrases.push(words);
+                lexicon.entries.push((entry, kind));
+            }
+        }
+        Ok(lexicon)
+    }
+}
+
+/// Score a comment is queued or hidden at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Thresholds {
+    pub queue: u8,
+    pub hide: u8,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Thresholds { queue: DEFAULT_QUEUE_THRESHOLD, hide: DEFAULT_HIDE_THRESHOLD }
+    }
+}
+
+impl Thresholds {
+    /// Read `TOXICITY_QUEUE_THRESHOLD` and `TOXICITY_HIDE_THRESHOLD`.
+    /// Values above 100 switch that action off.
+    pub fn from_env() -> Result<Self, String> {
+        let read = |name: &str, default: u8| match std::env::var(name) {
+            Ok(value) if !value.trim().is_empty() => {
+                value.trim().parse::<u8>().map_err(|_| format!("Invalid {}: {}", name, value))
+            }
+            _ => Ok(default),
+        };
+        let thresholds = Thresholds {
+            queue: read("TOXICITY_QUEUE_THRESHOLD", DEFAULT_QUEUE_THRESHOLD)?,
+            hide: read("TOXICITY_HIDE_THRESHOLD", DEFAULT_HIDE_THRESHOLD)?,
+        };
+        if thresholds.queue > thresholds.hide {
+            return Err("TOXICITY_QUEUE_THRESHOLD must not exceed TOXICITY_HIDE_THRESHOLD".to_string());
+        }
+        Ok(thresholds)
+    }
+
+    pub fn moderation(&self, score: u8) -> ModerationStatus {
+        if score >= self.hide {
+            ModerationStatus::Hidden
+        } else if score >= self.queue {
+            ModerationStatus::Queued
+        } else {
+            ModerationStatus::Visible
+        }
+    }
+}
+
+/// Outcome of classifying a piece of text.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Toxicity {
+    /// 0–100; 0 = nothing abusive found.
+    pub score: u8,
+    /// Abusive terms, in text order.
+    pub terms: Vec<ToxicTerm>,
+}
+
+impl Toxicity {
+    pub fn categories(&self) -> Vec<ToxicityCategory> {
+        let mut categories: Vec<ToxicityCategory> = self.terms.iter().map(|t| t.category).collect();
+        categories.sort();
+        categories.dedup();
+        categories
+    }
+}
+
+/// Undo common letter-for-digit spellings ("sh1t").
+fn unleet(word: &str) -> String {
+    word.chars()
+        .map(|c| match c {
+            '0' => 'o',
+            '1' => 'i',
+            '3' => 'e',
+            '4' => 'a',
+            '5' => 's',
+            '7' => 't',
+            other => other,
+        })
+        .collect()
+}
+
+/// Rewrite tokens that are not lexicon words but become one with digits
+/// read as letters or stretched letters shortened.
+fn normalize_tokens(lexicon: &ToxicityLexicon, tokens: &mut [Token]) {
+    for token in tokens.iter_mut().filter(|t| !lexicon.words.contains(&t.text)) {
+        let mut candidates = Vec::new();
+        if token.text.chars().any(|c| c.is_ascii_digit()) && token.text.chars().any(char::is_alphabetic) {
+            candidates.push(unleet(&token.text));
+        }
+        candidates.push(collapse_runs(&token.text, 1));
+        if let Some(word) = candidates.into_iter().find(|w| lexicon.words.contains(w)) {
+            token.text = word;
+        }
+    }
+}
+
+/// Word-list classifier with moderation thresholds.
+#[derive(Debug, Clone)]
+pub struct ToxicityClassifier {
+    lexicon: ToxicityLexicon,
+    thresholds: Thresholds,
+}
+
+impl ToxicityClassifier {
+    pub fn new(lexicon: ToxicityLexicon, thresholds: Thresholds) -> Self {
+        ToxicityClassifier { lexicon, thresholds }
+    }
+
+    pub fn thresholds(&self) -> Thresholds {
+        self.thresholds
+    }
+
+    /// An insult is aimed at a person when a target word is part of it or
+    /// comes shortly before it.
+    fn is_targeted(&self, tokens: &[Token], start: usize, end: usize) -> bool {
+        tokens[start.saturating_sub(TARGET_SCOPE)..end].iter().any(|t| self.lexicon.targets.contains(&t.text))
+    }
+
+    pub fn classify(&self, text: &str) -> Toxicity {
+        let mut tokens = tokenize(text);
+        normalize_tokens(&self.lexicon, &mut tokens);
+
+        let mut total = 0.0;
+        let mut terms = Vec::new();
+        for m in match_phrases(&tokens, &self.lexicon.phrases, false) {
+            let (entry, kind) = &self.lexicon.entries[m.phrase];
+            let (category, weight) = match kind {
+                Kind::Profanity => (ToxicityCategory::Profanity, PROFANITY_WEIGHT),
+                Kind::Insult if self.is_targeted(&tokens, m.start, m.start + m.len) => {
+                    (ToxicityCategory::Attack, ATTACK_WEIGHT)
+                }
+                Kind::Insult => (ToxicityCategory::Attack, UNTARGETED_INSULT_WEIGHT),
+                Kind::Threat => (ToxicityCategory::Threat, THREAT_WEIGHT),
+                Kind::Slur => (ToxicityCategory::Slur, SLUR_WEIGHT),
+            };
+            total += weight;
+            terms.push(ToxicTerm {
+                term: entry.clone(),
+                category,
+                start: tokens[m.start].start,
+                end: tokens[m.start + m.len - 1].end,
+            });
+        }
+
+        Toxicity { score: (total * SCORE_PER_WEIGHT).round().min(100.0) as u8, terms }
+    }
+
+    /// Where a comment with this toxicity goes.
+    pub fn moderation(&self, toxicity: &Toxicity) -> ModerationStatus {
+        self.thresholds.moderation(toxicity.score)
+    }
+}
+
+static CLASSIFIER: OnceCell<ToxicityClassifier> = OnceCell::new();
+
+fn built_in() -> ToxicityLexicon {
+    ToxicityLexicon::parse(BUILT_IN).expect("built-in toxicity lexicon is valid")
+}
+
+/// Load the lexicon named by `TOXICITY_LEXICON` (default: built-in) and the
+/// thresholds. Call once at startup; an invalid file or threshold stops
+/// the server.
+pub fn init_toxicity() -> Result<(), String> {
+    let lexicon = match std::env::var("TOXICITY_LEXICON") {
+        Ok(path) if !path.trim().is_empty() => {
+            let source = std::fs::read_to_string(path.trim()).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+            ToxicityLexicon::parse(&source).map_err(|e| format!("{}: {}", path, e))?
+        }
+        _ => built_in(),
+    };
+    let thresholds = Thresholds::from_env()?;
+    log::info!(
+        "Toxicity lexicon {}: queue at {}, hide at {}",
+        lexicon.version,
+        thresholds.queue,
+        thresholds.hide
+    );
+
+    CLASSIFIER
+        .set(ToxicityClassifier::new(lexicon, thresholds))
+        .map_err(|_| "Toxicity classifier already initialized".to_string())
+}
+
+/// The configured classifier, falling back to the built-in lexicon and
+/// default thresholds if `init_toxicity()` was never called.
+pub fn classifier() -> &'static ToxicityClassifier {
+    CLASSIFIER.get_or_init(|| ToxicityClassifier::new(built_in(), Thresholds::default()))
+}
+
+/// Classify `text` and decide its moderation status.
+pub fn assess(text: &str) -> (Toxicity, ModerationStatus) {
+    let classifier = classifier();
+    let toxicity = classifier.classify(text);
+    let moderation = classifier.moderation(&toxicity);
+    (toxicity, moderation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify(text: &str) -> Toxicity {
+        ToxicityClassifier::new(built_in(), Thresholds::default()).classify(text)
+    }
+
+    #[test]
+    fn test_complaints_are_not_toxic() {
+        for text in ["The soup was cold and the waiter was rude", "I hate cold fries, angry about the bill"] {
+            assert_eq!(classify(text), Toxicity::default(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_categories_and_scores() {
+        let toxicity = classify("The food was shit");
+        assert_eq!(toxicity.score, 25);
+        assert_eq!(toxicity.categories(), [ToxicityCategory::Profanity]);
+
+        assert_eq!(classify("stupid prices").score, 13);
+        let toxicity = classify("You are an idiot");
+        assert_eq!(toxicity.score, 50);
+        assert_eq!(toxicity.terms[0].category, ToxicityCategory::Attack);
+
+        let text = "Come back and I'll kill you, you fucking clown";
+        let toxicity = classify(text);
+        let terms: Vec<&str> = toxicity.terms.iter().map(|t| &text[t.start..t.end]).collect();
+        assert_eq!(terms, ["I'll kill", "fucking", "clown"]);
+        assert_eq!(toxicity.score, 100);
+    }
+
+    #[test]
+    fn test_obfuscated_spellings() {
+        assert_eq!(classify("sh1t service").score, 25);
+        assert_eq!(classify("Shiiiit").score, 25);
+    }
+
+    #[test]
+    fn test_custom_lexicon_and_thresholds() {
+        let lexicon = ToxicityLexicon::parse("version = \"test\"\nslurs = [\"zorp\"]").unwrap();
+        let classifier = ToxicityClassifier::new(lexicon, Thresholds { queue: 20, hide: 90 });
+        let toxicity = classifier.classify("what a zorp");
+        assert_eq!(toxicity.categories(), [ToxicityCategory::Slur]);
+        assert_eq!(classifier.moderation(&toxicity), ModerationStatus::Hidden);
+        assert_eq!(classifier.moderation(&Toxicity { score: 20, terms: Vec::new() }), ModerationStatus::Queued);
+        assert_eq!(classifier.moderation(&Toxicity::default()), ModerationStatus::Visible);
+
+        assert!(ToxicityLexicon::parse("version = \"x\"\nprofanity = [\"!!\"]").is_err());
+    }
+}